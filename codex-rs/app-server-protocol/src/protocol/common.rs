@@ -1674,6 +1674,8 @@ server_notification_definitions! {
     #[experimental("turn/moderationMetadata")]
     TurnModerationMetadata => "turn/moderationMetadata" (v2::TurnModerationMetadataNotification),
     ModelSafetyBufferingUpdated => "model/safetyBuffering/updated" (v2::ModelSafetyBufferingUpdatedNotification),
+    // Fork: provider attribution for Chat Completions responses.
+    ModelServedBy => "model/servedBy" (v2::ModelServedByNotification),
     Warning => "warning" (v2::WarningNotification),
     GuardianWarning => "guardianWarning" (v2::GuardianWarningNotification),
     DeprecationNotice => "deprecationNotice" (v2::DeprecationNoticeNotification),
@@ -3438,6 +3440,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn serialize_model_served_by_notification() -> Result<()> {
+        let notification = ServerNotification::ModelServedBy(v2::ModelServedByNotification {
+            thread_id: "thr_123".to_string(),
+            turn_id: "turn_123".to_string(),
+            provider: "openrouter".to_string(),
+            model: "anthropic/claude-sonnet-4".to_string(),
+        });
+        assert_eq!(
+            json!({
+                "method": "model/servedBy",
+                "params": {
+                    "threadId": "thr_123",
+                    "turnId": "turn_123",
+                    "provider": "openrouter",
+                    "model": "anthropic/claude-sonnet-4"
+                }
+            }),
+            serde_json::to_value(&notification)?,
+        );
+        Ok(())
+    }
+
     #[test]
     fn serialize_thread_realtime_output_audio_delta_notification() -> Result<()> {
         let notification = ServerNotification::ThreadRealtimeOutputAudioDelta(
@@ -208,3 +208,14 @@ pub struct ModelSafetyBufferingUpdatedNotification {
     pub show_buffering_ui: bool,
     pub faster_model: Option<String>,
 }
+
+// Fork: the provider and model that served a Chat Completions response.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ModelServedByNotification {
+    pub thread_id: String,
+    pub turn_id: String,
+    pub provider: String,
+    pub model: String,
+}
@@ -689,6 +689,7 @@ mod thread_processor_behavior_tests {
             requires_openai_auth: false,
             supports_websockets: true,
             system_role: None,
+            compat: None,
         };
         let config_manager = ConfigManager::new(
             temp_dir.path().to_path_buf(),
@@ -87,6 +87,12 @@ pub enum ResponseEvent {
     /// meaning the server already accounted for past reasoning tokens and the
     /// client should not re-estimate them.
     ServerReasoningIncluded(bool),
+    /// Fork: names the provider and model that served a Chat Completions
+    /// response. Sent immediately before `Completed`.
+    ServedBy {
+        provider: String,
+        model: String,
+    },
     Completed {
         response_id: String,
         token_usage: Option<TokenUsage>,
@@ -3,24 +3,35 @@ use crate::common::ResponseStream;
 use crate::endpoint::session::EndpointSession;
 use crate::error::ApiError;
 use crate::provider::Provider;
+use crate::provider::ReasoningEffortSupport;
+use crate::provider::ReasoningTags;
 use crate::requests::chat_compat::ChatRequestBuilder;
 use crate::sse::chat_compat::ChatReasoningFormat;
+use crate::sse::chat_compat::ChatStreamOptions;
+use crate::sse::chat_compat::ThinkTagConfig;
+use crate::sse::chat_compat::spawn_chat_completion_response;
 use crate::sse::chat_compat::spawn_chat_stream;
+use crate::sse::chat_compat::with_served_by;
 use crate::telemetry::SseTelemetry;
 use codex_client::EncodedJsonBody;
 use codex_client::HttpTransport;
 use codex_client::RequestCompression;
 use codex_client::RequestTelemetry;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::protocol::SessionSource;
 use http::HeaderValue;
 use http::Method;
 use serde_json::Value;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
 
 pub struct ChatCompatClient<T: HttpTransport> {
     session: EndpointSession<T>,
     sse_telemetry: Option<Arc<dyn SseTelemetry>>,
+    cancellation: Option<CancellationToken>,
+    provider_id: Option<String>,
 }
 
 impl<T: HttpTransport> ChatCompatClient<T> {
@@ -28,6 +39,8 @@ impl<T: HttpTransport> ChatCompatClient<T> {
         Self {
             session: EndpointSession::new(transport, provider, auth),
             sse_telemetry: None,
+            cancellation: None,
+            provider_id: None,
         }
     }
 
@@ -39,6 +52,26 @@ impl<T: HttpTransport> ChatCompatClient<T> {
         Self {
             session: self.session.with_request_telemetry(request),
             sse_telemetry: sse,
+            cancellation: self.cancellation,
+            provider_id: self.provider_id,
+        }
+    }
+
+    /// Fork: stop streaming when `cancellation` fires, flushing the assistant
+    /// and reasoning text received so far as completed items.
+    pub fn with_cancellation(self, cancellation: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(cancellation),
+            ..self
+        }
+    }
+
+    /// Fork: report `provider_id` (the `model_providers` key) in
+    /// `ResponseEvent::ServedBy` instead of the provider's display name.
+    pub fn with_provider_id(self, provider_id: String) -> Self {
+        Self {
+            provider_id: Some(provider_id),
+            ..self
         }
     }
 
@@ -48,21 +81,63 @@ impl<T: HttpTransport> ChatCompatClient<T> {
         instructions: &str,
         input: &[ResponseItem],
         tools: &[Value],
+        effort: Option<&ReasoningEffort>,
         conversation_id: Option<String>,
         session_source: Option<SessionSource>,
     ) -> Result<ResponseStream, ApiError> {
         let provider = self.session.provider();
         let reasoning_format = chat_reasoning_format(provider);
+        let stream_options = ChatStreamOptions::for_provider(provider);
+        let served_by = self
+            .provider_id
+            .clone()
+            .unwrap_or_else(|| provider.name.clone());
         let mut request = ChatRequestBuilder::new(model, instructions, input, tools)
             .conversation_id(conversation_id)
             .session_source(session_source)
             .build(provider)?;
 
         merge_split_assistant_messages(&mut request.body);
+        // Fork: keep each assistant message within the provider's tool-call cap.
+        if let Some(max) = provider.compat.max_tool_calls_per_message {
+            split_oversized_tool_call_messages(&mut request.body, max.max(1));
+        }
+
+        // Fork: send reasoning effort in the form the provider accepts.
+        inject_reasoning_effort(
+            &mut request.body,
+            provider.reasoning_effort_support(),
+            effort,
+        );
+        // Fork: stream tool-call arguments where the provider supports it.
+        if provider.sends_tool_stream() {
+            inject_tool_stream(&mut request.body);
+        }
 
-        // Fork: Zhipu requires thinking + tool_stream parameters.
-        if provider.is_zhipu() {
-            inject_zhipu_params(&mut request.body);
+        // Fork: providers without SSE support get a single JSON completion
+        // that is adapted into the same event stream.
+        if !provider.compat.supports_streaming {
+            disable_streaming(&mut request.body);
+            let response = self
+                .session
+                .execute_with(
+                    Method::POST,
+                    "chat/completions",
+                    request.headers,
+                    Some(request.body),
+                    |req| {
+                        req.compression = RequestCompression::None;
+                    },
+                )
+                .await?;
+            let stream = spawn_chat_completion_response(
+                response.body,
+                provider.stream_idle_timeout,
+                self.sse_telemetry.clone(),
+                reasoning_format,
+                stream_options,
+            )?;
+            return Ok(with_served_by(stream, served_by, model.to_string()));
         }
 
         let body = EncodedJsonBody::encode(&request.body).map_err(|e| {
@@ -86,37 +161,117 @@ impl<T: HttpTransport> ChatCompatClient<T> {
             )
             .await?;
 
-        Ok(spawn_chat_stream(
+        let stream = spawn_chat_stream(
             stream_response,
             provider.stream_idle_timeout,
             self.sse_telemetry.clone(),
             reasoning_format,
+            stream_options,
+            self.cancellation.clone(),
             None,
-        ))
+        );
+        Ok(with_served_by(stream, served_by, model.to_string()))
     }
 }
 
-fn chat_reasoning_format(_provider: &Provider) -> ChatReasoningFormat {
+fn chat_reasoning_format(provider: &Provider) -> ChatReasoningFormat {
     // Keep think tags in assistant content rather than extracting them into
-    // separate Reasoning items.  MiniMax (and potentially other Chat
-    // Completions providers) relies on seeing its own `<think>` tags inline;
-    // extracting them corrupts the conversation history round-trip because
-    // reasoning attached to tool-call messages (where content is null) is
-    // lost entirely.
-    ChatReasoningFormat::Standard
+    // separate Reasoning items unless the provider opts in.  MiniMax (and
+    // potentially other Chat Completions providers) relies on seeing its own
+    // `<think>` tags inline; extracting them corrupts the conversation history
+    // round-trip because reasoning attached to tool-call messages (where
+    // content is null) is lost entirely.
+    match provider.reasoning_tags() {
+        Some(tags) => ChatReasoningFormat::ThinkTags(think_tags(tags)),
+        None => ChatReasoningFormat::Standard,
+    }
 }
 
-/// Fork: Inject Zhipu-specific parameters into the Chat Completions request body.
+/// Fork: the delimiters for a provider's inline reasoning tags.
+fn think_tags(tags: ReasoningTags) -> ThinkTagConfig {
+    match tags {
+        ReasoningTags::Think => ThinkTagConfig::THINK,
+        ReasoningTags::Thinking => ThinkTagConfig::THINKING,
+        ReasoningTags::Reasoning => ThinkTagConfig::REASONING,
+    }
+}
+
+/// Fork: Inject reasoning effort into the Chat Completions request body.
 ///
-/// Zhipu requires `thinking: {"type": "enabled"}` to activate reasoning and
-/// `tool_stream: true` to enable streaming of tool call arguments.
-fn inject_zhipu_params(body: &mut serde_json::Value) {
+/// Toggle providers such as Zhipu need `thinking: {"type": "enabled"}` to
+/// activate reasoning and cannot grade it, so every effort other than an
+/// explicit `none` maps to `enabled`. Level providers accept only `low`,
+/// `medium` and `high`, so other efforts are sent as the nearest of those.
+fn inject_reasoning_effort(
+    body: &mut serde_json::Value,
+    support: ReasoningEffortSupport,
+    effort: Option<&ReasoningEffort>,
+) {
+    let Some(obj) = body.as_object_mut() else {
+        return;
+    };
+    match support {
+        ReasoningEffortSupport::Unsupported => {}
+        ReasoningEffortSupport::Levels => {
+            if let Some(effort) = effort {
+                match nearest_effort_level(effort) {
+                    Some(level) => {
+                        if level != effort.as_str() {
+                            debug!("mapping reasoning effort {effort} to {level}");
+                        }
+                        obj.insert("reasoning_effort".into(), serde_json::json!(level));
+                    }
+                    None => debug!("omitting reasoning effort {effort}: no matching level"),
+                }
+            }
+        }
+        ReasoningEffortSupport::Toggle => {
+            let thinking = if effort == Some(&ReasoningEffort::None) {
+                "disabled"
+            } else {
+                "enabled"
+            };
+            if let Some(effort) = effort
+                && !matches!(effort, ReasoningEffort::None)
+            {
+                debug!("mapping reasoning effort {effort} to thinking {thinking}");
+            }
+            obj.insert("thinking".into(), serde_json::json!({"type": thinking}));
+        }
+    }
+}
+
+/// Fork: the `low`/`medium`/`high` level closest to `effort`, or `None` when
+/// the field should be omitted: for `none`, and for model-defined efforts that
+/// level providers would reject.
+fn nearest_effort_level(effort: &ReasoningEffort) -> Option<&'static str> {
+    match effort {
+        ReasoningEffort::None | ReasoningEffort::Custom(_) => None,
+        ReasoningEffort::Minimal | ReasoningEffort::Low => Some("low"),
+        ReasoningEffort::Medium => Some("medium"),
+        ReasoningEffort::High
+        | ReasoningEffort::XHigh
+        | ReasoningEffort::Max
+        | ReasoningEffort::Ultra => Some("high"),
+    }
+}
+
+/// Fork: Request streaming of tool call arguments with `tool_stream: true`.
+fn inject_tool_stream(body: &mut serde_json::Value) {
     if let Some(obj) = body.as_object_mut() {
-        obj.insert("thinking".into(), serde_json::json!({"type": "enabled"}));
         obj.insert("tool_stream".into(), serde_json::json!(true));
     }
 }
 
+/// Fork: Switch a Chat Completions request body to non-streaming mode.
+fn disable_streaming(body: &mut serde_json::Value) {
+    if let Some(obj) = body.as_object_mut() {
+        obj.insert("stream".into(), serde_json::json!(false));
+        obj.remove("stream_options");
+        obj.remove("tool_stream");
+    }
+}
+
 /// Merge consecutive assistant messages where the first has content and the
 /// second has `tool_calls`.
 ///
@@ -169,12 +324,213 @@ fn merge_split_assistant_messages(body: &mut serde_json::Value) {
     }
 }
 
+/// Fork: Split assistant messages carrying more than `max` tool calls.
+///
+/// Some providers reject history where one assistant message has more tool
+/// calls than they allow in parallel. Oversized messages become consecutive
+/// assistant messages of at most `max` calls, each followed by the tool
+/// results for its calls. Content and reasoning stay on the first message.
+fn split_oversized_tool_call_messages(body: &mut serde_json::Value, max: usize) {
+    let Some(messages) = body.get_mut("messages").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    let mut i = 0;
+    while i < messages.len() {
+        let oversized = messages[i].get("role").and_then(Value::as_str) == Some("assistant")
+            && messages[i]
+                .get("tool_calls")
+                .and_then(Value::as_array)
+                .is_some_and(|calls| calls.len() > max);
+        if !oversized {
+            i += 1;
+            continue;
+        }
+
+        let mut results_end = i + 1;
+        while results_end < messages.len()
+            && messages[results_end].get("role").and_then(Value::as_str) == Some("tool")
+        {
+            results_end += 1;
+        }
+        let mut results: Vec<Value> = messages.drain(i + 1..results_end).collect();
+        let mut first = Some(messages.remove(i));
+        let calls = first
+            .as_mut()
+            .and_then(|message| message.get_mut("tool_calls"))
+            .and_then(Value::as_array_mut)
+            .map(std::mem::take)
+            .unwrap_or_default();
+
+        let mut split = Vec::new();
+        for chunk in calls.chunks(max) {
+            let mut message = first
+                .take()
+                .unwrap_or_else(|| serde_json::json!({"role": "assistant", "content": null}));
+            message["tool_calls"] = Value::Array(chunk.to_vec());
+            split.push(message);
+            for call in chunk {
+                let id = call.get("id");
+                let (matching, rest): (Vec<Value>, Vec<Value>) = results
+                    .into_iter()
+                    .partition(|result| id.is_some() && result.get("tool_call_id") == id);
+                results = rest;
+                split.extend(matching);
+            }
+        }
+        // Results without a matching call keep their place after the calls.
+        split.extend(results);
+
+        let split_len = split.len();
+        messages.splice(i..i, split);
+        i += split_len;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
+    fn provider(name: &str, tool_stream: Option<bool>) -> Provider {
+        Provider {
+            name: name.to_string(),
+            base_url: "https://example.com/v1".to_string(),
+            query_params: None,
+            headers: http::HeaderMap::new(),
+            retry: crate::provider::RetryConfig {
+                max_attempts: 1,
+                base_delay: std::time::Duration::from_millis(1),
+                retry_429: false,
+                retry_5xx: false,
+                retry_transport: false,
+            },
+            stream_idle_timeout: std::time::Duration::from_secs(1),
+            system_role: None,
+            compat: crate::provider::ProviderCompat {
+                tool_stream,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn tool_stream_follows_provider_compat() {
+        assert!(provider("Zhipu", None).sends_tool_stream());
+        assert!(!provider("Zhipu", Some(false)).sends_tool_stream());
+        assert!(!provider("MiniMax", None).sends_tool_stream());
+        assert!(provider("MiniMax", Some(true)).sends_tool_stream());
+
+        let mut body = json!({"model": "m"});
+        inject_tool_stream(&mut body);
+        assert_eq!(body, json!({"model": "m", "tool_stream": true}));
+    }
+
+    #[test]
+    fn reasoning_effort_maps_to_provider_support() {
+        assert_eq!(
+            provider("Zhipu", None).reasoning_effort_support(),
+            ReasoningEffortSupport::Toggle
+        );
+        assert_eq!(
+            provider("MiniMax", None).reasoning_effort_support(),
+            ReasoningEffortSupport::Unsupported
+        );
+
+        let body_for = |support, effort: Option<ReasoningEffort>| {
+            let mut body = json!({"model": "m"});
+            inject_reasoning_effort(&mut body, support, effort.as_ref());
+            body
+        };
+        assert_eq!(
+            body_for(ReasoningEffortSupport::Toggle, Some(ReasoningEffort::High)),
+            json!({"model": "m", "thinking": {"type": "enabled"}})
+        );
+        assert_eq!(
+            body_for(ReasoningEffortSupport::Toggle, None),
+            json!({"model": "m", "thinking": {"type": "enabled"}})
+        );
+        assert_eq!(
+            body_for(ReasoningEffortSupport::Toggle, Some(ReasoningEffort::None)),
+            json!({"model": "m", "thinking": {"type": "disabled"}})
+        );
+        assert_eq!(
+            body_for(ReasoningEffortSupport::Levels, Some(ReasoningEffort::High)),
+            json!({"model": "m", "reasoning_effort": "high"})
+        );
+        assert_eq!(
+            body_for(ReasoningEffortSupport::Levels, Some(ReasoningEffort::XHigh)),
+            json!({"model": "m", "reasoning_effort": "high"})
+        );
+        assert_eq!(
+            body_for(
+                ReasoningEffortSupport::Levels,
+                Some(ReasoningEffort::Minimal)
+            ),
+            json!({"model": "m", "reasoning_effort": "low"})
+        );
+        assert_eq!(
+            body_for(ReasoningEffortSupport::Levels, Some(ReasoningEffort::None)),
+            json!({"model": "m"})
+        );
+        assert_eq!(
+            body_for(
+                ReasoningEffortSupport::Levels,
+                Some(ReasoningEffort::Custom("turbo".to_string()))
+            ),
+            json!({"model": "m"})
+        );
+        assert_eq!(
+            body_for(
+                ReasoningEffortSupport::Unsupported,
+                Some(ReasoningEffort::High)
+            ),
+            json!({"model": "m"})
+        );
+    }
+
+    #[test]
+    fn reasoning_format_selects_provider_tags() {
+        assert_eq!(
+            chat_reasoning_format(&provider("DeepSeek", None)),
+            ChatReasoningFormat::ThinkTags(ThinkTagConfig::THINKING)
+        );
+        assert_eq!(
+            chat_reasoning_format(&provider("MiniMax", None)),
+            ChatReasoningFormat::Standard
+        );
+        assert_eq!(
+            chat_reasoning_format(&provider("Zhipu", None)),
+            ChatReasoningFormat::Standard
+        );
+        let configured = Provider {
+            compat: crate::provider::ProviderCompat {
+                reasoning_tags: Some(ReasoningTags::Reasoning),
+                ..Default::default()
+            },
+            ..provider("Custom", None)
+        };
+        assert_eq!(
+            chat_reasoning_format(&configured),
+            ChatReasoningFormat::ThinkTags(ThinkTagConfig::REASONING)
+        );
+    }
+
+    #[test]
+    fn disable_streaming_drops_stream_only_params() {
+        let mut body = json!({
+            "model": "m",
+            "stream": true,
+            "stream_options": {"include_usage": true},
+            "tool_stream": true
+        });
+
+        disable_streaming(&mut body);
+
+        assert_eq!(body, json!({"model": "m", "stream": false}));
+    }
+
     #[test]
     fn merges_split_assistant_content_and_tool_calls() {
         let mut body = json!({
@@ -279,4 +635,45 @@ mod tests {
         assert_eq!(messages[2]["content"], "second");
         assert!(messages[2]["tool_calls"].is_array());
     }
+
+    #[test]
+    fn splits_assistant_messages_over_tool_call_cap() {
+        let call = |id: &str| json!({"id": id, "type": "function", "function": {"name": "f", "arguments": "{}"}});
+        let result = |id: &str| json!({"role": "tool", "tool_call_id": id, "content": id});
+        let mut body = json!({
+            "messages": [
+                {"role": "user", "content": "go"},
+                {"role": "assistant", "content": "working", "reasoning": "plan", "tool_calls": [
+                    call("c1"), call("c2"), call("c3")
+                ]},
+                result("c1"),
+                result("c2"),
+                result("c3"),
+                {"role": "assistant", "content": null, "tool_calls": [call("c4"), call("c5")]},
+                result("c4"),
+                result("c5")
+            ]
+        });
+
+        split_oversized_tool_call_messages(&mut body, 2);
+
+        assert_eq!(
+            body,
+            json!({
+                "messages": [
+                    {"role": "user", "content": "go"},
+                    {"role": "assistant", "content": "working", "reasoning": "plan", "tool_calls": [
+                        call("c1"), call("c2")
+                    ]},
+                    result("c1"),
+                    result("c2"),
+                    {"role": "assistant", "content": null, "tool_calls": [call("c3")]},
+                    result("c3"),
+                    {"role": "assistant", "content": null, "tool_calls": [call("c4"), call("c5")]},
+                    result("c4"),
+                    result("c5")
+                ]
+            })
+        );
+    }
 }
@@ -142,6 +142,7 @@ mod tests {
                 retry_transport: true,
             },
             system_role: None,
+            compat: Default::default(),
             stream_idle_timeout: Duration::from_secs(1),
         }
     }
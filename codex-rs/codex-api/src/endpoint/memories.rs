@@ -150,6 +150,7 @@ mod tests {
             },
             stream_idle_timeout: Duration::from_secs(1),
             system_role: None,
+            compat: Default::default(),
         }
     }
 
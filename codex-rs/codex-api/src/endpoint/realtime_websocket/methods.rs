@@ -674,7 +674,7 @@ impl RealtimeWebsocketClient {
             .into_client_request()
             .map_err(|err| ApiError::Stream(format!("failed to build websocket request: {err}")))?;
         let headers = merge_request_headers(
-            &self.provider.headers,
+            &self.provider.request_headers(),
             with_session_id_header(extra_headers, config.session_id.as_deref())?,
             default_headers,
         );
@@ -1742,6 +1742,7 @@ mod tests {
             },
             stream_idle_timeout: Duration::from_secs(5),
             system_role: None,
+            compat: Default::default(),
         };
         let client = RealtimeWebsocketClient::new(provider);
         let connection = client
@@ -2067,6 +2068,7 @@ mod tests {
             },
             stream_idle_timeout: Duration::from_secs(5),
             system_role: None,
+            compat: Default::default(),
         };
         let client = RealtimeWebsocketClient::new(provider);
         let connection = client
@@ -2193,6 +2195,7 @@ mod tests {
             },
             stream_idle_timeout: Duration::from_secs(5),
             system_role: None,
+            compat: Default::default(),
         };
         let client = RealtimeWebsocketClient::new(provider);
         let connection = client
@@ -2298,6 +2301,7 @@ mod tests {
             },
             stream_idle_timeout: Duration::from_secs(5),
             system_role: None,
+            compat: Default::default(),
         };
         let client = RealtimeWebsocketClient::new(provider);
         let connection = client
@@ -2389,6 +2393,7 @@ mod tests {
             },
             stream_idle_timeout: Duration::from_secs(5),
             system_role: None,
+            compat: Default::default(),
         };
         let client = RealtimeWebsocketClient::new(provider);
         let connection = client
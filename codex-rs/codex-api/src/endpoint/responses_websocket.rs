@@ -345,7 +345,7 @@ impl ResponsesWebsocketClient {
             .map_err(|err| ApiError::Stream(format!("failed to build websocket URL: {err}")))?;
 
         let mut headers =
-            merge_request_headers(&self.provider.headers, extra_headers, default_headers);
+            merge_request_headers(&self.provider.request_headers(), extra_headers, default_headers);
         self.auth.add_auth_headers(&mut headers);
 
         let (stream, _status, server_reasoning_included, models_etag, server_model) =
@@ -379,7 +379,7 @@ impl ResponsesWebsocketClient {
             .map_err(|err| ApiError::Stream(format!("failed to build websocket URL: {err}")))?;
 
         let mut headers =
-            merge_request_headers(&self.provider.headers, extra_headers, default_headers);
+            merge_request_headers(&self.provider.request_headers(), extra_headers, default_headers);
         self.auth.add_auth_headers(&mut headers);
 
         let (mut stream, status, reasoning_included, models_etag, server_model) =
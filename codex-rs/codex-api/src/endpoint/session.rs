@@ -10,10 +10,17 @@ use codex_client::RequestTelemetry;
 use codex_client::Response;
 use codex_client::StreamResponse;
 use codex_client::TransportError;
+use futures::StreamExt;
 use http::HeaderMap;
 use http::Method;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::sync::PoisonError;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
 use tracing::instrument;
 
 pub(crate) struct EndpointSession<T: HttpTransport> {
@@ -21,15 +28,36 @@ pub(crate) struct EndpointSession<T: HttpTransport> {
     provider: Provider,
     auth: SharedAuthProvider,
     request_telemetry: Option<Arc<dyn RequestTelemetry>>,
+    /// Fork: enforces `compat.max_concurrent_requests` for the provider.
+    request_limiter: Option<Arc<Semaphore>>,
+}
+
+/// Fork: returns the process-wide limiter for `provider`, so the limit holds
+/// across every client and sub-agent talking to it.
+fn request_limiter(provider: &Provider) -> Option<Arc<Semaphore>> {
+    type Limiters = Mutex<HashMap<(String, usize), Arc<Semaphore>>>;
+    static LIMITERS: OnceLock<Limiters> = OnceLock::new();
+
+    let limit = provider.compat.max_concurrent_requests?.max(1);
+    let mut limiters = LIMITERS
+        .get_or_init(Limiters::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let limiter = limiters
+        .entry((provider.name.clone(), limit))
+        .or_insert_with(|| Arc::new(Semaphore::new(limit)));
+    Some(Arc::clone(limiter))
 }
 
 impl<T: HttpTransport> EndpointSession<T> {
     pub(crate) fn new(transport: T, provider: Provider, auth: SharedAuthProvider) -> Self {
+        let request_limiter = request_limiter(&provider);
         Self {
             transport,
             provider,
             auth,
             request_telemetry: None,
+            request_limiter,
         }
     }
 
@@ -45,6 +73,13 @@ impl<T: HttpTransport> EndpointSession<T> {
         &self.provider
     }
 
+    /// Waits for a free request slot when the provider has a concurrency
+    /// limit. The slot is released when the returned permit is dropped.
+    async fn acquire_request_permit(&self) -> Option<OwnedSemaphorePermit> {
+        let limiter = self.request_limiter.clone()?;
+        limiter.acquire_owned().await.ok()
+    }
+
     fn make_request(
         &self,
         method: &Method,
@@ -95,6 +130,7 @@ impl<T: HttpTransport> EndpointSession<T> {
             req
         };
 
+        let _permit = self.acquire_request_permit().await;
         let response = run_with_request_telemetry(
             self.provider.retry.to_policy(),
             self.request_telemetry.clone(),
@@ -136,7 +172,8 @@ impl<T: HttpTransport> EndpointSession<T> {
         let request = request.into_prepared().map_err(TransportError::Build)?;
         let make_request = || request.clone();
 
-        let stream = run_with_request_telemetry(
+        let permit = self.acquire_request_permit().await;
+        let mut stream = run_with_request_telemetry(
             self.provider.retry.to_policy(),
             self.request_telemetry.clone(),
             make_request,
@@ -151,6 +188,165 @@ impl<T: HttpTransport> EndpointSession<T> {
         )
         .await?;
 
+        // Fork: keep the request slot until the response body is dropped.
+        if let Some(permit) = permit {
+            stream.bytes = stream
+                .bytes
+                .inspect(move |_| {
+                    let _ = &permit;
+                })
+                .boxed();
+        }
+
         Ok(stream)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthProvider;
+    use crate::provider::ProviderCompat;
+    use crate::provider::RetryConfig;
+    use http::StatusCode;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct DummyAuth;
+
+    impl AuthProvider for DummyAuth {
+        fn add_auth_headers(&self, _headers: &mut HeaderMap) {}
+    }
+
+    /// Holds every request open until `gate` hands out a permit.
+    #[derive(Clone)]
+    struct GatedTransport {
+        started: Arc<AtomicUsize>,
+        gate: Arc<Semaphore>,
+    }
+
+    impl HttpTransport for GatedTransport {
+        async fn execute(&self, _req: Request) -> Result<Response, TransportError> {
+            self.started.fetch_add(1, Ordering::SeqCst);
+            let _open = self.gate.acquire().await.expect("gate open");
+            Ok(Response {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: bytes::Bytes::new(),
+            })
+        }
+
+        async fn stream(&self, _req: Request) -> Result<StreamResponse, TransportError> {
+            Err(TransportError::Build("stream should not run".to_string()))
+        }
+    }
+
+    /// Records the URL of every request it receives.
+    #[derive(Clone, Default)]
+    struct RecordingTransport {
+        urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HttpTransport for RecordingTransport {
+        async fn execute(&self, req: Request) -> Result<Response, TransportError> {
+            self.urls.lock().expect("urls lock").push(req.url);
+            Ok(Response {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: bytes::Bytes::new(),
+            })
+        }
+
+        async fn stream(&self, _req: Request) -> Result<StreamResponse, TransportError> {
+            Err(TransportError::Build("stream should not run".to_string()))
+        }
+    }
+
+    fn provider(name: &str, max_concurrent_requests: Option<usize>) -> Provider {
+        Provider {
+            name: name.to_string(),
+            base_url: "https://example.com/v1".to_string(),
+            query_params: None,
+            headers: HeaderMap::new(),
+            retry: RetryConfig {
+                max_attempts: 1,
+                base_delay: Duration::from_millis(1),
+                retry_429: false,
+                retry_5xx: false,
+                retry_transport: false,
+            },
+            stream_idle_timeout: Duration::from_secs(1),
+            system_role: None,
+            compat: ProviderCompat {
+                max_concurrent_requests,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn third_request_waits_when_limit_is_two() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let gate = Arc::new(Semaphore::new(0));
+        let transport = GatedTransport {
+            started: Arc::clone(&started),
+            gate: Arc::clone(&gate),
+        };
+        // Each session gets its own client, as sub-agents do; the limit is
+        // still shared because it is keyed by provider.
+        let sessions: Vec<_> = (0..3)
+            .map(|_| {
+                EndpointSession::new(
+                    transport.clone(),
+                    provider("gated-test", Some(2)),
+                    Arc::new(DummyAuth),
+                )
+            })
+            .collect();
+
+        let requests = futures::future::join_all(sessions.iter().map(|session| {
+            session.execute(Method::POST, "chat/completions", HeaderMap::new(), None)
+        }));
+        let check = async {
+            while started.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(started.load(Ordering::SeqCst), 2);
+            gate.add_permits(3);
+        };
+        let (results, ()) = tokio::join!(requests, check);
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(started.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn configured_query_params_are_appended_to_request_url() {
+        let transport = RecordingTransport::default();
+        let mut provider = provider("query-test", None);
+        provider.query_params = Some(HashMap::from([(
+            "api-version".to_string(),
+            "2024-06-01".to_string(),
+        )]));
+        let session = EndpointSession::new(transport.clone(), provider, Arc::new(DummyAuth));
+
+        session
+            .execute(Method::POST, "chat/completions", HeaderMap::new(), None)
+            .await
+            .expect("request succeeds");
+
+        assert_eq!(
+            *transport.urls.lock().expect("urls lock"),
+            vec!["https://example.com/v1/chat/completions?api-version=2024-06-01".to_string()]
+        );
+    }
+
+    #[test]
+    fn unlimited_provider_has_no_limiter() {
+        assert!(request_limiter(&provider("unlimited-test", None)).is_none());
+    }
+}
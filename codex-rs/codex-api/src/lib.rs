@@ -78,6 +78,9 @@ pub use crate::images::ImageQuality;
 pub use crate::images::ImageResponse;
 pub use crate::images::ImageUrl;
 pub use crate::provider::Provider;
+pub use crate::provider::ProviderCompat;
+pub use crate::provider::ReasoningEffortSupport;
+pub use crate::provider::ReasoningTags;
 pub use crate::provider::RetryConfig;
 pub use crate::provider::is_azure_responses_provider;
 pub use crate::requests::Compression;
@@ -2,8 +2,13 @@ use codex_client::Request;
 use codex_client::RequestCompression;
 use codex_client::RetryOn;
 use codex_client::RetryPolicy;
+use http::HeaderValue;
 use http::Method;
 use http::header::HeaderMap;
+use http::header::HeaderName;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::time::Duration;
 use url::Url;
@@ -35,6 +40,88 @@ impl RetryConfig {
     }
 }
 
+/// Fork: how a Chat Completions provider accepts reasoning effort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningEffortSupport {
+    /// Reasoning effort is not sent.
+    Unsupported,
+    /// Graded levels are sent as `reasoning_effort`.
+    Levels,
+    /// Reasoning can only be switched on or off with
+    /// `thinking: {"type": "enabled" | "disabled"}`.
+    Toggle,
+}
+
+/// Fork: tags a Chat Completions provider wraps inline reasoning in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningTags {
+    /// `<think>...</think>`.
+    Think,
+    /// `<thinking>...</thinking>`.
+    Thinking,
+    /// `<reasoning>...</reasoning>`.
+    Reasoning,
+}
+
+/// Fork: resolved behavior overrides for OpenAI-compatible providers whose
+/// Chat Completions endpoint deviates from the reference API.
+#[derive(Debug, Clone)]
+pub struct ProviderCompat {
+    /// When false, Chat Completions requests are sent with `stream: false`
+    /// and the single JSON response is adapted into the streaming events.
+    pub supports_streaming: bool,
+    /// When true, `finish_reason: "tool_calls"` ends the stream after at most
+    /// a short wait for trailing usage, instead of waiting for a `[DONE]`
+    /// sentinel the provider never sends.
+    pub tool_calls_finish_terminal: bool,
+    /// When true, Chat Completions tool-call ids are replaced with synthetic
+    /// ids that are unique across turns.
+    pub synthesize_tool_call_ids: bool,
+    /// When true, Chat Completions tool-call arguments that are not valid
+    /// JSON are repaired where possible (trailing commas, single quotes).
+    pub repair_tool_call_arguments: bool,
+    /// Whether Chat Completions requests ask for streamed tool-call arguments
+    /// with `tool_stream: true`. `None` keeps the built-in default, which is
+    /// on for Zhipu only.
+    pub tool_stream: Option<bool>,
+    /// How reasoning effort is sent on Chat Completions requests. `None`
+    /// keeps the built-in default: toggle for Zhipu, unsupported otherwise.
+    pub reasoning_effort: Option<ReasoningEffortSupport>,
+    /// Tags around reasoning inlined in assistant content, which is then
+    /// split out into reasoning items. `None` keeps the built-in default:
+    /// `<thinking>` for DeepSeek, left inline otherwise.
+    pub reasoning_tags: Option<ReasoningTags>,
+    /// Maximum number of in-flight requests to this provider across every
+    /// session in the process. Further requests wait for a free slot.
+    pub max_concurrent_requests: Option<usize>,
+    /// Maximum number of tool calls a single assistant message may carry in
+    /// Chat Completions history. Larger messages are split before sending.
+    pub max_tool_calls_per_message: Option<usize>,
+    /// `(header name, env var)` pairs re-read on every request so rotated
+    /// values are picked up without rebuilding the provider. An unset or
+    /// empty variable omits the header.
+    pub env_http_headers: Vec<(String, String)>,
+}
+
+impl Default for ProviderCompat {
+    fn default() -> Self {
+        Self {
+            supports_streaming: true,
+            tool_calls_finish_terminal: false,
+            synthesize_tool_call_ids: false,
+            repair_tool_call_arguments: false,
+            tool_stream: None,
+            reasoning_effort: None,
+            reasoning_tags: None,
+            max_concurrent_requests: None,
+            max_tool_calls_per_message: None,
+            env_http_headers: Vec::new(),
+        }
+    }
+}
+
 /// HTTP endpoint configuration used to talk to a concrete API deployment.
 ///
 /// Encapsulates base URL, default headers, query params, retry policy, and
@@ -50,6 +137,8 @@ pub struct Provider {
     /// Role name to use for system/instruction messages in chat completions.
     /// Defaults to `"system"` when `None`.
     pub system_role: Option<String>,
+    /// Fork: provider-specific Chat Completions behavior overrides.
+    pub compat: ProviderCompat,
 }
 
 impl Provider {
@@ -81,13 +170,43 @@ impl Provider {
         Request {
             method,
             url: self.url_for_path(path),
-            headers: self.headers.clone(),
+            headers: self.request_headers(),
             body: None,
             compression: RequestCompression::None,
             timeout: None,
         }
     }
 
+    /// Fork: the static headers plus env-sourced headers resolved from the
+    /// current environment. Use this instead of `headers` when sending.
+    pub fn request_headers(&self) -> HeaderMap {
+        let mut headers = self.headers.clone();
+        self.apply_env_http_headers(&mut headers);
+        headers
+    }
+
+    /// Fork: resolve env-sourced headers, dropping those whose variable is
+    /// unset or empty.
+    fn apply_env_http_headers(&self, headers: &mut HeaderMap) {
+        for (header, env_var) in &self.compat.env_http_headers {
+            let Ok(name) = HeaderName::try_from(header.as_str()) else {
+                continue;
+            };
+            match std::env::var(env_var)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .map(HeaderValue::try_from)
+            {
+                Some(Ok(value)) => {
+                    headers.insert(name, value);
+                }
+                _ => {
+                    headers.remove(name);
+                }
+            }
+        }
+    }
+
     /// Role name to use for system/instruction messages in chat completions.
     pub fn effective_system_role(&self) -> &str {
         self.system_role.as_deref().unwrap_or("system")
@@ -114,6 +233,32 @@ impl Provider {
         self.name.eq_ignore_ascii_case("zhipu") || self.base_url.contains("bigmodel.cn")
     }
 
+    /// Check if this provider is DeepSeek (inlines reasoning in `<thinking>` tags).
+    pub fn is_deepseek(&self) -> bool {
+        self.name.eq_ignore_ascii_case("deepseek") || self.base_url.contains("deepseek.com")
+    }
+
+    /// Fork: whether to send `tool_stream: true` on Chat Completions requests.
+    pub fn sends_tool_stream(&self) -> bool {
+        self.compat.tool_stream.unwrap_or_else(|| self.is_zhipu())
+    }
+
+    /// Fork: how reasoning effort is sent on Chat Completions requests.
+    pub fn reasoning_effort_support(&self) -> ReasoningEffortSupport {
+        self.compat.reasoning_effort.unwrap_or(if self.is_zhipu() {
+            ReasoningEffortSupport::Toggle
+        } else {
+            ReasoningEffortSupport::Unsupported
+        })
+    }
+
+    /// Fork: the tags to extract inline reasoning from, if any.
+    pub fn reasoning_tags(&self) -> Option<ReasoningTags> {
+        self.compat
+            .reasoning_tags
+            .or_else(|| self.is_deepseek().then_some(ReasoningTags::Thinking))
+    }
+
     pub fn is_azure_responses_endpoint(&self) -> bool {
         is_azure_responses_provider(&self.name, Some(&self.base_url))
     }
@@ -158,6 +303,46 @@ fn matches_azure_responses_base_url(base_url: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn build_request_rereads_env_http_headers() {
+        const ENV_VAR: &str = "CODEX_API_TEST_ROTATING_PROJECT_TOKEN";
+        let provider = Provider {
+            name: "test".to_string(),
+            base_url: "https://example.com/v1".to_string(),
+            query_params: None,
+            headers: HeaderMap::new(),
+            retry: RetryConfig {
+                max_attempts: 1,
+                base_delay: Duration::from_millis(1),
+                retry_429: false,
+                retry_5xx: false,
+                retry_transport: false,
+            },
+            stream_idle_timeout: Duration::from_secs(1),
+            system_role: None,
+            compat: ProviderCompat {
+                env_http_headers: vec![("X-Project-Token".to_string(), ENV_VAR.to_string())],
+                ..Default::default()
+            },
+        };
+        let header = |provider: &Provider| {
+            provider
+                .build_request(Method::POST, "chat/completions")
+                .headers
+                .get("X-Project-Token")
+                .map(|value| value.to_str().unwrap().to_string())
+        };
+
+        // SAFETY: the variable name is unique to this test.
+        unsafe { std::env::set_var(ENV_VAR, "token-1") };
+        assert_eq!(header(&provider), Some("token-1".to_string()));
+        unsafe { std::env::set_var(ENV_VAR, "token-2") };
+        assert_eq!(header(&provider), Some("token-2".to_string()));
+        unsafe { std::env::remove_var(ENV_VAR) };
+        assert_eq!(header(&provider), None);
+    }
 
     #[test]
     fn detects_azure_responses_base_urls() {
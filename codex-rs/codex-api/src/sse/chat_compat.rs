@@ -2,11 +2,16 @@ use crate::common::ResponseEvent;
 use crate::common::ResponseStream;
 use crate::error::ApiError;
 use crate::sse::chat_compat_fork::ContentSegment;
+use crate::sse::chat_compat_fork::SyntheticToolCallIds;
 use crate::sse::chat_compat_fork::ThinkTagStreamSplitter;
+use crate::sse::chat_compat_fork::chat_completion_to_sse_body;
+use crate::sse::chat_compat_fork::find_chat_usage;
+use crate::sse::chat_compat_fork::repair_tool_call_arguments;
 use crate::telemetry::SseTelemetry;
 use codex_client::StreamResponse;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ReasoningItemReasoningSummary;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::TokenUsage;
 use eventsource_stream::Eventsource;
@@ -20,16 +25,25 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::Instant;
 use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 use tracing::trace;
 
 pub(crate) use crate::sse::chat_compat_fork::ChatReasoningFormat;
+pub(crate) use crate::sse::chat_compat_fork::ChatStreamOptions;
+pub(crate) use crate::sse::chat_compat_fork::ThinkTagConfig;
+
+/// Fork: how long a terminal tool-call finish waits for a trailing usage
+/// chunk before completing without token usage.
+const TRAILING_USAGE_GRACE: Duration = Duration::from_millis(500);
 
 pub(crate) fn spawn_chat_stream(
     stream_response: StreamResponse,
     idle_timeout: Duration,
     telemetry: Option<Arc<dyn SseTelemetry>>,
     reasoning_format: ChatReasoningFormat,
+    options: ChatStreamOptions,
+    cancellation: Option<CancellationToken>,
     _turn_state: Option<Arc<OnceLock<String>>>,
 ) -> ResponseStream {
     let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
@@ -40,6 +54,8 @@ pub(crate) fn spawn_chat_stream(
             idle_timeout,
             telemetry,
             reasoning_format,
+            options,
+            cancellation,
         )
         .await;
     });
@@ -49,6 +65,71 @@ pub(crate) fn spawn_chat_stream(
     }
 }
 
+/// Fork: adapts a non-streaming Chat Completions response body into the same
+/// event stream `spawn_chat_stream` produces, for providers that do not
+/// support `stream: true`.
+pub(crate) fn spawn_chat_completion_response(
+    body: bytes::Bytes,
+    idle_timeout: Duration,
+    telemetry: Option<Arc<dyn SseTelemetry>>,
+    reasoning_format: ChatReasoningFormat,
+    options: ChatStreamOptions,
+) -> Result<ResponseStream, ApiError> {
+    let completion: serde_json::Value = serde_json::from_slice(&body).map_err(|err| {
+        ApiError::Stream(format!("failed to decode chat completions response: {err}"))
+    })?;
+    let sse_body = bytes::Bytes::from(chat_completion_to_sse_body(&completion));
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        process_chat_sse_with_format(
+            futures::stream::iter([Ok(sse_body)]),
+            tx_event,
+            idle_timeout,
+            telemetry,
+            reasoning_format,
+            options,
+            /*cancellation*/ None,
+        )
+        .await;
+    });
+    Ok(ResponseStream {
+        rx_event,
+        upstream_request_id: None,
+    })
+}
+
+/// Fork: forwards `stream`, sending `ResponseEvent::ServedBy` with the given
+/// provider and model right before `Completed` so callers can attribute the
+/// turn.
+pub(crate) fn with_served_by(
+    mut stream: ResponseStream,
+    provider: String,
+    model: String,
+) -> ResponseStream {
+    let upstream_request_id = stream.upstream_request_id.take();
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        while let Some(event) = stream.rx_event.recv().await {
+            if matches!(event, Ok(ResponseEvent::Completed { .. })) {
+                let served_by = ResponseEvent::ServedBy {
+                    provider: provider.clone(),
+                    model: model.clone(),
+                };
+                if tx_event.send(Ok(served_by)).await.is_err() {
+                    return;
+                }
+            }
+            if tx_event.send(event).await.is_err() {
+                return;
+            }
+        }
+    });
+    ResponseStream {
+        rx_event,
+        upstream_request_id,
+    }
+}
+
 /// Processes Server-Sent Events from the Chat Completions streaming API.
 ///
 /// Handles `data: [DONE]` and `data: DONE` sentinels, tool call accumulation,
@@ -67,16 +148,24 @@ pub async fn process_chat_sse<S>(
         idle_timeout,
         telemetry,
         ChatReasoningFormat::Standard,
+        ChatStreamOptions::default(),
+        /*cancellation*/ None,
     )
     .await;
 }
 
+/// Fork: when `cancellation` fires, the assistant and reasoning text received
+/// so far is sent as `OutputItemDone` items and the stream ends without
+/// `Completed`. Partial tool calls are dropped since their arguments may be
+/// incomplete.
 async fn process_chat_sse_with_format<S>(
     stream: S,
     tx_event: mpsc::Sender<Result<ResponseEvent, ApiError>>,
     idle_timeout: Duration,
     telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
     reasoning_format: ChatReasoningFormat,
+    options: ChatStreamOptions,
+    cancellation: Option<CancellationToken>,
 ) where
     S: Stream<Item = Result<bytes::Bytes, codex_client::TransportError>> + Unpin,
 {
@@ -97,14 +186,26 @@ async fn process_chat_sse_with_format<S>(
     let mut last_tool_call_index: Option<usize> = None;
     let mut assistant_item: Option<ResponseItem> = None;
     let mut reasoning_item: Option<ResponseItem> = None;
+    // Fork: whether the pending assistant item was started before the
+    // reasoning item; `None` until only one of them has been started.
+    let mut assistant_started_first: Option<bool> = None;
     let mut content_splitter = ThinkTagStreamSplitter::new(reasoning_format);
     let mut token_usage: Option<TokenUsage> = None;
+    let synthetic_tool_call_ids = options
+        .synthesize_tool_call_ids
+        .then(SyntheticToolCallIds::new);
+    let resolve_call_id = |id: Option<String>, index: usize| match (&synthetic_tool_call_ids, id) {
+        (Some(synthetic), _) => synthetic.id_for(index),
+        (None, Some(id)) => id,
+        (None, None) => format!("tool-call-{index}"),
+    };
 
     async fn flush_and_complete(
         tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
         content_splitter: &mut ThinkTagStreamSplitter,
         reasoning_item: &mut Option<ResponseItem>,
         assistant_item: &mut Option<ResponseItem>,
+        assistant_started_first: &mut Option<bool>,
         token_usage: Option<TokenUsage>,
     ) {
         append_content_segments(
@@ -115,17 +216,13 @@ async fn process_chat_sse_with_format<S>(
         )
         .await;
 
-        if let Some(reasoning) = reasoning_item.take() {
-            let _ = tx_event
-                .send(Ok(ResponseEvent::OutputItemDone(reasoning)))
-                .await;
-        }
-
-        if let Some(assistant) = assistant_item.take() {
-            let _ = tx_event
-                .send(Ok(ResponseEvent::OutputItemDone(assistant)))
-                .await;
-        }
+        send_done_items(
+            tx_event,
+            reasoning_item,
+            assistant_item,
+            assistant_started_first,
+        )
+        .await;
 
         let _ = tx_event
             .send(Ok(ResponseEvent::Completed {
@@ -138,7 +235,27 @@ async fn process_chat_sse_with_format<S>(
 
     loop {
         let start = Instant::now();
-        let response = timeout(idle_timeout, stream.next()).await;
+        let response = tokio::select! {
+            biased;
+            () = wait_for_cancellation(cancellation.as_ref()) => {
+                append_content_segments(
+                    &tx_event,
+                    &mut assistant_item,
+                    &mut reasoning_item,
+                    content_splitter.flush_remaining(),
+                )
+                .await;
+                send_done_items(
+                    &tx_event,
+                    &mut reasoning_item,
+                    &mut assistant_item,
+                    &mut assistant_started_first,
+                )
+                .await;
+                return;
+            }
+            response = timeout(idle_timeout, stream.next()) => response,
+        };
         if let Some(t) = telemetry.as_ref() {
             t.on_sse_poll(&response, start.elapsed());
         }
@@ -154,6 +271,7 @@ async fn process_chat_sse_with_format<S>(
                     &mut content_splitter,
                     &mut reasoning_item,
                     &mut assistant_item,
+                    &mut assistant_started_first,
                     token_usage.take(),
                 )
                 .await;
@@ -181,6 +299,7 @@ async fn process_chat_sse_with_format<S>(
                 &mut content_splitter,
                 &mut reasoning_item,
                 &mut assistant_item,
+                &mut assistant_started_first,
                 token_usage.take(),
             )
             .await;
@@ -198,7 +317,7 @@ async fn process_chat_sse_with_format<S>(
             }
         };
 
-        if let Some(usage_val) = value.get("usage") {
+        if let Some(usage_val) = find_chat_usage(&value) {
             token_usage = parse_chat_usage(usage_val);
         }
 
@@ -209,16 +328,7 @@ async fn process_chat_sse_with_format<S>(
         for choice in choices {
             if let Some(delta) = choice.get("delta") {
                 if let Some(reasoning) = delta.get("reasoning") {
-                    if let Some(text) = reasoning.as_str() {
-                        append_reasoning_text(&tx_event, &mut reasoning_item, text.to_string())
-                            .await;
-                    } else if let Some(text) = reasoning.get("text").and_then(|v| v.as_str()) {
-                        append_reasoning_text(&tx_event, &mut reasoning_item, text.to_string())
-                            .await;
-                    } else if let Some(text) = reasoning.get("content").and_then(|v| v.as_str()) {
-                        append_reasoning_text(&tx_event, &mut reasoning_item, text.to_string())
-                            .await;
-                    }
+                    append_reasoning_value(&tx_event, &mut reasoning_item, reasoning).await;
                 }
 
                 // Fork: Zhipu/DeepSeek use `reasoning_content` as a top-level
@@ -243,7 +353,12 @@ async fn process_chat_sse_with_format<S>(
                                 .await;
                             }
                         }
-                    } else if let Some(text) = content.as_str() {
+                    } else if let Some(text) = content
+                        .as_str()
+                        // Fork: some providers stream a single content-part
+                        // object instead of a string or an array of parts.
+                        .or_else(|| content.get("text").and_then(|t| t.as_str()))
+                    {
                         append_content_segments(
                             &tx_event,
                             &mut assistant_item,
@@ -254,6 +369,12 @@ async fn process_chat_sse_with_format<S>(
                     }
                 }
 
+                note_item_order(
+                    &mut assistant_started_first,
+                    &assistant_item,
+                    &reasoning_item,
+                );
+
                 if let Some(tool_call_values) = delta.get("tool_calls").and_then(|c| c.as_array()) {
                     for tool_call in tool_call_values {
                         let mut index = tool_call
@@ -262,9 +383,19 @@ async fn process_chat_sse_with_format<S>(
                             .map(|i| i as usize);
 
                         let mut call_id_for_lookup = None;
-                        if let Some(call_id) = tool_call.get("id").and_then(|i| i.as_str()) {
+                        // Empty ids carry no identity; treat them as absent so
+                        // distinct calls are not merged under one key.
+                        let tool_call_id = tool_call
+                            .get("id")
+                            .and_then(|i| i.as_str())
+                            .filter(|id| !id.is_empty());
+                        if let Some(call_id) = tool_call_id {
                             call_id_for_lookup = Some(call_id.to_string());
-                            if let Some(existing) = tool_call_index_by_id.get(call_id) {
+                            // Providers that reuse ids across distinct calls are
+                            // keyed by their explicit index instead.
+                            if let Some(existing) = tool_call_index_by_id.get(call_id)
+                                && (index.is_none() || !options.synthesize_tool_call_ids)
+                            {
                                 index = Some(*existing);
                             }
                         }
@@ -287,7 +418,7 @@ async fn process_chat_sse_with_format<S>(
                             tool_call_order.push(index);
                         }
 
-                        if let Some(id) = tool_call.get("id").and_then(|i| i.as_str()) {
+                        if let Some(id) = tool_call_id {
                             call_state.id.get_or_insert_with(|| id.to_string());
                             tool_call_index_by_id.entry(id.to_string()).or_insert(index);
                         }
@@ -312,13 +443,12 @@ async fn process_chat_sse_with_format<S>(
             if let Some(message) = choice.get("message")
                 && let Some(reasoning) = message.get("reasoning")
             {
-                if let Some(text) = reasoning.as_str() {
-                    append_reasoning_text(&tx_event, &mut reasoning_item, text.to_string()).await;
-                } else if let Some(text) = reasoning.get("text").and_then(|v| v.as_str()) {
-                    append_reasoning_text(&tx_event, &mut reasoning_item, text.to_string()).await;
-                } else if let Some(text) = reasoning.get("content").and_then(|v| v.as_str()) {
-                    append_reasoning_text(&tx_event, &mut reasoning_item, text.to_string()).await;
-                }
+                append_reasoning_value(&tx_event, &mut reasoning_item, reasoning).await;
+                note_item_order(
+                    &mut assistant_started_first,
+                    &assistant_item,
+                    &reasoning_item,
+                );
             }
 
             let finish_reason = choice.get("finish_reason").and_then(|r| r.as_str());
@@ -331,17 +461,13 @@ async fn process_chat_sse_with_format<S>(
                 )
                 .await;
 
-                if let Some(reasoning) = reasoning_item.take() {
-                    let _ = tx_event
-                        .send(Ok(ResponseEvent::OutputItemDone(reasoning)))
-                        .await;
-                }
-
-                if let Some(assistant) = assistant_item.take() {
-                    let _ = tx_event
-                        .send(Ok(ResponseEvent::OutputItemDone(assistant)))
-                        .await;
-                }
+                send_done_items(
+                    &tx_event,
+                    &mut reasoning_item,
+                    &mut assistant_item,
+                    &mut assistant_started_first,
+                )
+                .await;
 
                 // Some providers (e.g. MiniMax) use finish_reason "stop" even
                 // when tool calls are present. Emit any accumulated tool calls
@@ -360,12 +486,17 @@ async fn process_chat_sse_with_format<S>(
                         debug!("Skipping tool call at index {index} because name is missing");
                         continue;
                     };
+                    let arguments = if options.repair_tool_call_arguments {
+                        repair_tool_call_arguments(arguments)
+                    } else {
+                        arguments
+                    };
                     let item = ResponseItem::FunctionCall {
                         id: None,
                         name,
                         namespace: None,
                         arguments,
-                        call_id: id.unwrap_or_else(|| format!("tool-call-{index}")),
+                        call_id: resolve_call_id(id, index),
                         internal_chat_message_metadata_passthrough: None,
                     };
                     let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
@@ -378,6 +509,24 @@ async fn process_chat_sse_with_format<S>(
             }
 
             if finish_reason == Some("length") {
+                // Fork: surface whatever was generated before the cutoff so
+                // partial output is visible for debugging and recovery.
+                append_content_segments(
+                    &tx_event,
+                    &mut assistant_item,
+                    &mut reasoning_item,
+                    content_splitter.flush_remaining(),
+                )
+                .await;
+
+                send_done_items(
+                    &tx_event,
+                    &mut reasoning_item,
+                    &mut assistant_item,
+                    &mut assistant_started_first,
+                )
+                .await;
+
                 let _ = tx_event.send(Err(ApiError::ContextWindowExceeded)).await;
                 return;
             }
@@ -391,11 +540,19 @@ async fn process_chat_sse_with_format<S>(
                 )
                 .await;
 
+                note_item_order(
+                    &mut assistant_started_first,
+                    &assistant_item,
+                    &reasoning_item,
+                );
                 if let Some(reasoning) = reasoning_item.take() {
                     let _ = tx_event
                         .send(Ok(ResponseEvent::OutputItemDone(reasoning)))
                         .await;
                 }
+                // Any reasoning started from here on follows the pending
+                // assistant item.
+                assistant_started_first = assistant_item.is_some().then_some(true);
 
                 for index in tool_call_order.drain(..) {
                     let Some(state) = tool_calls.remove(&index) else {
@@ -411,16 +568,47 @@ async fn process_chat_sse_with_format<S>(
                         debug!("Skipping tool call at index {index} because name is missing");
                         continue;
                     };
+                    let arguments = if options.repair_tool_call_arguments {
+                        repair_tool_call_arguments(arguments)
+                    } else {
+                        arguments
+                    };
                     let item = ResponseItem::FunctionCall {
                         id: None,
                         name,
                         namespace: None,
                         arguments,
-                        call_id: id.unwrap_or_else(|| format!("tool-call-{index}")),
+                        call_id: resolve_call_id(id, index),
                         internal_chat_message_metadata_passthrough: None,
                     };
                     let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
                 }
+
+                // Fork: providers that never send `[DONE]` after a tool-call
+                // finish would otherwise leave the turn waiting for the idle
+                // timeout. Usage often follows in its own chunk, so wait
+                // briefly for it; past the grace period the turn completes
+                // without usage rather than stalling.
+                if options.tool_calls_finish_terminal {
+                    if token_usage.is_none() {
+                        token_usage = read_trailing_usage(
+                            &mut stream,
+                            TRAILING_USAGE_GRACE,
+                            cancellation.as_ref(),
+                        )
+                        .await;
+                    }
+                    flush_and_complete(
+                        &tx_event,
+                        &mut content_splitter,
+                        &mut reasoning_item,
+                        &mut assistant_item,
+                        &mut assistant_started_first,
+                        token_usage.take(),
+                    )
+                    .await;
+                    return;
+                }
             }
         }
     }
@@ -444,6 +632,80 @@ async fn append_content_segments(
     }
 }
 
+/// Fork: reads events for up to `grace` until one carries usage. Returns
+/// `None` on `[DONE]`, stream end, an error, cancellation or timeout.
+async fn read_trailing_usage<S, E>(
+    stream: &mut S,
+    grace: Duration,
+    cancellation: Option<&CancellationToken>,
+) -> Option<TokenUsage>
+where
+    S: Stream<Item = Result<eventsource_stream::Event, E>> + Unpin,
+{
+    let deadline = Instant::now() + grace;
+    loop {
+        let sse = tokio::select! {
+            biased;
+            () = wait_for_cancellation(cancellation) => return None,
+            response = tokio::time::timeout_at(deadline, stream.next()) => match response {
+                Ok(Some(Ok(sse))) => sse,
+                _ => return None,
+            },
+        };
+        let data = sse.data.trim();
+        if data == "[DONE]" || data == "DONE" {
+            return None;
+        }
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(data)
+            && let Some(usage) = find_chat_usage(&value)
+        {
+            return parse_chat_usage(usage);
+        }
+    }
+}
+
+/// Resolves when `cancellation` is cancelled; never resolves without a token.
+async fn wait_for_cancellation(cancellation: Option<&CancellationToken>) {
+    match cancellation {
+        Some(token) => token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Fork: records which of the pending items was started first, once exactly
+/// one of them exists.
+fn note_item_order(
+    assistant_started_first: &mut Option<bool>,
+    assistant_item: &Option<ResponseItem>,
+    reasoning_item: &Option<ResponseItem>,
+) {
+    if assistant_started_first.is_none() && assistant_item.is_some() != reasoning_item.is_some() {
+        *assistant_started_first = Some(assistant_item.is_some());
+    }
+}
+
+/// Fork: sends `OutputItemDone` for the pending reasoning and assistant items
+/// in the order they were started. Reasoning goes first when the order is
+/// unknown, e.g. both began in the same chunk.
+async fn send_done_items(
+    tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    reasoning_item: &mut Option<ResponseItem>,
+    assistant_item: &mut Option<ResponseItem>,
+    assistant_started_first: &mut Option<bool>,
+) {
+    note_item_order(assistant_started_first, assistant_item, reasoning_item);
+    let reasoning = reasoning_item.take();
+    let assistant = assistant_item.take();
+    let items = if assistant_started_first.take() == Some(true) {
+        [assistant, reasoning]
+    } else {
+        [reasoning, assistant]
+    };
+    for item in items.into_iter().flatten() {
+        let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+    }
+}
+
 async fn append_assistant_text(
     tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
     assistant_item: &mut Option<ResponseItem>,
@@ -475,10 +737,32 @@ async fn append_assistant_text(
     }
 }
 
-async fn append_reasoning_text(
+/// Routes a `reasoning` field from a delta or message. A plain string, `text`
+/// or `content` is raw reasoning; `summary` is the provider's summarized
+/// reasoning and goes to the item's `summary` instead.
+async fn append_reasoning_value(
+    tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    reasoning_item: &mut Option<ResponseItem>,
+    reasoning: &serde_json::Value,
+) {
+    if let Some(text) = reasoning.get("summary").and_then(|v| v.as_str())
+        && !text.is_empty()
+    {
+        append_reasoning_summary_text(tx_event, reasoning_item, text.to_string()).await;
+    }
+
+    if let Some(text) = reasoning.as_str() {
+        append_reasoning_text(tx_event, reasoning_item, text.to_string()).await;
+    } else if let Some(text) = reasoning.get("text").and_then(|v| v.as_str()) {
+        append_reasoning_text(tx_event, reasoning_item, text.to_string()).await;
+    } else if let Some(text) = reasoning.get("content").and_then(|v| v.as_str()) {
+        append_reasoning_text(tx_event, reasoning_item, text.to_string()).await;
+    }
+}
+
+async fn ensure_reasoning_item(
     tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
     reasoning_item: &mut Option<ResponseItem>,
-    text: String,
 ) {
     if reasoning_item.is_none() {
         let item = ResponseItem::Reasoning {
@@ -493,6 +777,45 @@ async fn append_reasoning_text(
             .send(Ok(ResponseEvent::OutputItemAdded(item)))
             .await;
     }
+}
+
+async fn append_reasoning_summary_text(
+    tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    reasoning_item: &mut Option<ResponseItem>,
+    text: String,
+) {
+    ensure_reasoning_item(tx_event, reasoning_item).await;
+
+    if let Some(ResponseItem::Reasoning { summary, .. }) = reasoning_item {
+        if let Some(ReasoningItemReasoningSummary::SummaryText {
+            text: existing_text,
+        }) = summary.last_mut()
+        {
+            existing_text.push_str(&text);
+        } else {
+            summary.push(ReasoningItemReasoningSummary::SummaryText { text: text.clone() });
+            let _ = tx_event
+                .send(Ok(ResponseEvent::ReasoningSummaryPartAdded {
+                    summary_index: 0,
+                }))
+                .await;
+        }
+
+        let _ = tx_event
+            .send(Ok(ResponseEvent::ReasoningSummaryDelta {
+                delta: text,
+                summary_index: 0,
+            }))
+            .await;
+    }
+}
+
+async fn append_reasoning_text(
+    tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    reasoning_item: &mut Option<ResponseItem>,
+    text: String,
+) {
+    ensure_reasoning_item(tx_event, reasoning_item).await;
 
     if let Some(ResponseItem::Reasoning {
         content: Some(content),
@@ -593,6 +916,14 @@ mod tests {
     async fn collect_events_with_format(
         body: &str,
         reasoning_format: ChatReasoningFormat,
+    ) -> Vec<ResponseEvent> {
+        collect_events_with_options(body, reasoning_format, ChatStreamOptions::default()).await
+    }
+
+    async fn collect_events_with_options(
+        body: &str,
+        reasoning_format: ChatReasoningFormat,
+        options: ChatStreamOptions,
     ) -> Vec<ResponseEvent> {
         let reader = ReaderStream::new(std::io::Cursor::new(body.to_string()))
             .map_err(|err| codex_client::TransportError::Network(err.to_string()));
@@ -603,6 +934,8 @@ mod tests {
             Duration::from_millis(1000),
             None,
             reasoning_format,
+            options,
+            /*cancellation*/ None,
         ));
 
         let mut out = Vec::new();
@@ -926,7 +1259,11 @@ mod tests {
         });
         let body = build_body(&[delta, finish]);
 
-        let events = collect_events_with_format(&body, ChatReasoningFormat::MinimaxThinkTags).await;
+        let events = collect_events_with_format(
+            &body,
+            ChatReasoningFormat::ThinkTags(ThinkTagConfig::THINK),
+        )
+        .await;
         assert_eq!(assistant_text_deltas(&events), vec!["visible".to_string()]);
         assert_eq!(reasoning_text_deltas(&events), vec!["internal".to_string()]);
     }
@@ -961,7 +1298,11 @@ mod tests {
         });
         let body = build_body(&[chunk_1, chunk_2, chunk_3, finish]);
 
-        let events = collect_events_with_format(&body, ChatReasoningFormat::MinimaxThinkTags).await;
+        let events = collect_events_with_format(
+            &body,
+            ChatReasoningFormat::ThinkTags(ThinkTagConfig::THINK),
+        )
+        .await;
         assert_eq!(assistant_text_deltas(&events), vec!["done".to_string()]);
         assert_eq!(reasoning_text_deltas(&events), vec!["alpha".to_string()]);
     }
@@ -1020,6 +1361,40 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn captures_text_from_single_content_part_objects() {
+        let chunk_1 = json!({
+            "choices": [{
+                "delta": {
+                    "content": {"type": "output_text", "text": "Hello, "}
+                }
+            }]
+        });
+        let chunk_2 = json!({
+            "choices": [{
+                "delta": {
+                    "content": {"type": "text", "text": "world"}
+                }
+            }]
+        });
+        let finish = json!({
+            "choices": [{
+                "finish_reason": "stop"
+            }]
+        });
+        let body = build_body(&[chunk_1, chunk_2, finish]);
+
+        let events = collect_events(&body).await;
+        assert_eq!(
+            assistant_text_deltas(&events),
+            vec!["Hello, ".to_string(), "world".to_string()]
+        );
+        assert_eq!(
+            assistant_output_text_parts(&events),
+            vec![vec!["Hello, world".to_string()]]
+        );
+    }
+
     #[tokio::test]
     async fn coalesces_reasoning_output_item_content_when_streaming_multiple_chunks() {
         let chunk_1 = json!({
@@ -1220,6 +1595,279 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn extracts_token_usage_from_alternate_usage_keys() {
+        let usage = json!({
+            "prompt_tokens": 7,
+            "completion_tokens": 3,
+            "total_tokens": 10
+        });
+        for usage_chunk in [
+            json!({ "choices": [], "response": { "usage": usage.clone() } }),
+            json!({ "choices": [], "x_usage": usage.clone() }),
+        ] {
+            let content = json!({
+                "choices": [{
+                    "delta": { "content": "hi" },
+                    "finish_reason": "stop"
+                }]
+            });
+            let mut body = build_body(&[content, usage_chunk]);
+            body.push_str("event: message\ndata: [DONE]\n\n");
+            let events = collect_events(&body).await;
+
+            let completed = events
+                .iter()
+                .find_map(|ev| match ev {
+                    ResponseEvent::Completed { token_usage, .. } => Some(token_usage.clone()),
+                    _ => None,
+                })
+                .expect("expected Completed event");
+
+            assert_eq!(
+                completed,
+                Some(TokenUsage {
+                    input_tokens: 7,
+                    cached_input_tokens: 0,
+                    output_tokens: 3,
+                    reasoning_output_tokens: 0,
+                    total_tokens: 10,
+                })
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn length_finish_flushes_partial_content_before_error() {
+        let content = json!({
+            "choices": [{
+                "delta": { "content": "partial answer" }
+            }]
+        });
+        let finish_length = json!({
+            "choices": [{
+                "finish_reason": "length"
+            }]
+        });
+
+        let body = build_body(&[content, finish_length]);
+        let reader = ReaderStream::new(std::io::Cursor::new(body))
+            .map_err(|err| codex_client::TransportError::Network(err.to_string()));
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
+        tokio::spawn(process_chat_sse_with_format(
+            reader,
+            tx,
+            Duration::from_millis(1000),
+            None,
+            ChatReasoningFormat::Standard,
+            ChatStreamOptions::default(),
+            /*cancellation*/ None,
+        ));
+
+        let mut events = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            events.push(ev);
+        }
+
+        assert_matches!(
+            &events[..],
+            [
+                Ok(ResponseEvent::OutputItemAdded(_)),
+                Ok(ResponseEvent::OutputTextDelta(_)),
+                Ok(ResponseEvent::OutputItemDone(ResponseItem::Message { role, .. })),
+                Err(ApiError::ContextWindowExceeded),
+            ] if role == "assistant"
+        );
+        assert_eq!(
+            assistant_output_text_parts(
+                &events
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .collect::<Vec<_>>()
+            ),
+            vec![vec!["partial answer".to_string()]]
+        );
+    }
+
+    #[tokio::test]
+    async fn done_items_follow_first_seen_order() {
+        let content = json!({
+            "choices": [{ "delta": { "content": "answer" } }]
+        });
+        let reasoning = json!({
+            "choices": [{ "delta": { "reasoning_content": "afterthought" } }]
+        });
+        let finish = json!({
+            "choices": [{ "finish_reason": "stop" }]
+        });
+
+        let done_kinds = |events: &[ResponseEvent]| {
+            events
+                .iter()
+                .filter_map(|ev| match ev {
+                    ResponseEvent::OutputItemDone(ResponseItem::Message { .. }) => Some("message"),
+                    ResponseEvent::OutputItemDone(ResponseItem::Reasoning { .. }) => {
+                        Some("reasoning")
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut body = build_body(&[content.clone(), reasoning.clone(), finish.clone()]);
+        body.push_str("event: message\ndata: [DONE]\n\n");
+        let events = collect_events(&body).await;
+        assert_eq!(done_kinds(&events), vec!["message", "reasoning"]);
+
+        let mut body = build_body(&[reasoning, content, finish]);
+        body.push_str("event: message\ndata: [DONE]\n\n");
+        let events = collect_events(&body).await;
+        assert_eq!(done_kinds(&events), vec!["reasoning", "message"]);
+    }
+
+    #[tokio::test]
+    async fn separates_reasoning_summary_from_reasoning_content() {
+        let summary_1 = json!({
+            "choices": [{ "delta": { "reasoning": { "summary": "Plan: " } } }]
+        });
+        let content = json!({
+            "choices": [{ "delta": { "reasoning": { "content": "raw thoughts" } } }]
+        });
+        let summary_2 = json!({
+            "choices": [{ "delta": { "reasoning": { "summary": "read the file" } } }]
+        });
+        let finish = json!({
+            "choices": [{ "finish_reason": "stop" }]
+        });
+
+        let mut body = build_body(&[summary_1, content, summary_2, finish]);
+        body.push_str("event: message\ndata: [DONE]\n\n");
+        let events = collect_events(&body).await;
+
+        let summary_deltas: Vec<String> = events
+            .iter()
+            .filter_map(|ev| match ev {
+                ResponseEvent::ReasoningSummaryDelta { delta, .. } => Some(delta.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            summary_deltas,
+            vec!["Plan: ".to_string(), "read the file".to_string()]
+        );
+        assert_eq!(
+            reasoning_text_deltas(&events),
+            vec!["raw thoughts".to_string()]
+        );
+
+        let reasoning = events
+            .iter()
+            .find_map(|ev| match ev {
+                ResponseEvent::OutputItemDone(item @ ResponseItem::Reasoning { .. }) => {
+                    Some(item.clone())
+                }
+                _ => None,
+            })
+            .expect("expected reasoning item");
+        assert_eq!(
+            reasoning,
+            ResponseItem::Reasoning {
+                id: None,
+                summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: "Plan: read the file".to_string(),
+                }],
+                content: Some(vec![ReasoningItemContent::ReasoningText {
+                    text: "raw thoughts".to_string(),
+                }]),
+                encrypted_content: None,
+                internal_chat_message_metadata_passthrough: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn synthesizes_unique_tool_call_ids_for_reused_or_empty_ids() {
+        let options = ChatStreamOptions {
+            synthesize_tool_call_ids: true,
+            ..Default::default()
+        };
+        // MiniMax-style quirks: one empty id and one id reused for a distinct
+        // call at another index.
+        let calls = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [
+                        { "index": 0, "id": "call_1", "function": { "name": "a", "arguments": "{}" } },
+                        { "index": 1, "id": "call_1", "function": { "name": "b", "arguments": "{}" } },
+                        { "index": 2, "id": "", "function": { "name": "c", "arguments": "{}" } }
+                    ]
+                }
+            }]
+        });
+        let finish = json!({ "choices": [{ "finish_reason": "tool_calls" }] });
+        let body = build_body(&[calls, finish]);
+
+        let call_ids = |events: Vec<ResponseEvent>| -> Vec<(String, String)> {
+            events
+                .into_iter()
+                .filter_map(|ev| match ev {
+                    ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                        name,
+                        call_id,
+                        ..
+                    }) => Some((name, call_id)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let first = call_ids(
+            collect_events_with_options(&body, ChatReasoningFormat::Standard, options).await,
+        );
+        let second = call_ids(
+            collect_events_with_options(&body, ChatReasoningFormat::Standard, options).await,
+        );
+
+        let names: Vec<&str> = first.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        let mut all_ids: Vec<&str> = first
+            .iter()
+            .chain(second.iter())
+            .map(|(_, call_id)| call_id.as_str())
+            .collect();
+        assert!(all_ids.iter().all(|call_id| !call_id.is_empty()));
+        all_ids.sort_unstable();
+        all_ids.dedup();
+        assert_eq!(
+            all_ids.len(),
+            6,
+            "ids must be unique across calls and turns"
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_tool_call_ids_do_not_merge_calls() {
+        let calls = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [
+                        { "index": 0, "id": "", "function": { "name": "a", "arguments": "{}" } },
+                        { "index": 1, "id": "", "function": { "name": "b", "arguments": "{}" } }
+                    ]
+                }
+            }]
+        });
+        let finish = json!({ "choices": [{ "finish_reason": "tool_calls" }] });
+        let events = collect_events(&build_body(&[calls, finish])).await;
+        assert_matches!(
+            &events[..],
+            [
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id: call_a, name: name_a, .. }),
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id: call_b, name: name_b, .. }),
+                ResponseEvent::Completed { .. }
+            ] if name_a == "a" && call_a == "tool-call-0" && name_b == "b" && call_b == "tool-call-1"
+        );
+    }
+
     #[tokio::test]
     async fn token_usage_is_none_when_no_usage_chunk() {
         let content = json!({
@@ -1247,4 +1895,332 @@ mod tests {
 
         assert_eq!(completed, None);
     }
+
+    #[tokio::test]
+    async fn served_by_is_reported_before_completion() {
+        let completion = json!({
+            "choices": [{
+                "message": { "role": "assistant", "content": "hi" },
+                "finish_reason": "stop"
+            }]
+        });
+        let stream = spawn_chat_completion_response(
+            bytes::Bytes::from(completion.to_string()),
+            Duration::from_millis(1000),
+            None,
+            ChatReasoningFormat::Standard,
+            ChatStreamOptions::default(),
+        )
+        .expect("valid completion");
+        let mut stream = with_served_by(stream, "minimax".to_string(), "MiniMax-M2.5".to_string());
+        let mut events = Vec::new();
+        while let Some(ev) = stream.rx_event.recv().await {
+            events.push(ev.expect("stream error"));
+        }
+
+        let tail = &events[events.len() - 2..];
+        assert_matches!(
+            tail,
+            [
+                ResponseEvent::ServedBy { provider, model },
+                ResponseEvent::Completed { .. },
+            ] if provider == "minimax" && model == "MiniMax-M2.5"
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|ev| matches!(ev, ResponseEvent::ServedBy { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn non_streaming_completion_matches_streamed_events() {
+        let completion = json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": "hi",
+                    "tool_calls": [{
+                        "id": "call_a",
+                        "type": "function",
+                        "function": { "name": "do_a", "arguments": "{\"foo\":1}" }
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {
+                "prompt_tokens": 3,
+                "completion_tokens": 2,
+                "total_tokens": 5
+            }
+        });
+
+        let mut stream = spawn_chat_completion_response(
+            bytes::Bytes::from(completion.to_string()),
+            Duration::from_millis(1000),
+            None,
+            ChatReasoningFormat::Standard,
+            ChatStreamOptions::default(),
+        )
+        .expect("valid completion");
+        let mut events = Vec::new();
+        while let Some(ev) = stream.rx_event.recv().await {
+            events.push(ev.expect("stream error"));
+        }
+
+        assert_eq!(assistant_text_deltas(&events), vec!["hi".to_string()]);
+        assert_matches!(
+            events.last(),
+            Some(ResponseEvent::Completed {
+                token_usage: Some(TokenUsage {
+                    total_tokens: 5,
+                    ..
+                }),
+                ..
+            })
+        );
+        assert!(events.iter().any(|ev| matches!(
+            ev,
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id, name, arguments, .. })
+                if call_id == "call_a" && name == "do_a" && arguments == "{\"foo\":1}"
+        )));
+    }
+
+    #[test]
+    fn non_streaming_completion_rejects_invalid_json() {
+        let result = spawn_chat_completion_response(
+            bytes::Bytes::from_static(b"not json"),
+            Duration::from_millis(1000),
+            None,
+            ChatReasoningFormat::Standard,
+            ChatStreamOptions::default(),
+        );
+        assert_matches!(result, Err(ApiError::Stream(_)));
+    }
+
+    #[tokio::test]
+    async fn tool_calls_finish_completes_without_done_when_terminal() {
+        let delta = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [{
+                        "id": "call_a",
+                        "function": { "name": "do_a", "arguments": "{}" }
+                    }]
+                }
+            }]
+        });
+        let finish = json!({
+            "choices": [{
+                "finish_reason": "tool_calls"
+            }]
+        });
+
+        // Keep the stream open after the finish chunk so only the terminal
+        // flag can end the turn before the idle timeout.
+        let body = build_body(&[delta, finish]);
+        let reader = ReaderStream::new(std::io::Cursor::new(body))
+            .map_err(|err| codex_client::TransportError::Network(err.to_string()))
+            .chain(futures::stream::pending());
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
+        tokio::spawn(process_chat_sse_with_format(
+            reader,
+            tx,
+            Duration::from_secs(60),
+            None,
+            ChatReasoningFormat::Standard,
+            ChatStreamOptions {
+                tool_calls_finish_terminal: true,
+                ..Default::default()
+            },
+            /*cancellation*/ None,
+        ));
+
+        let mut events = Vec::new();
+        let collect = async {
+            while let Some(ev) = rx.recv().await {
+                events.push(ev.expect("stream error"));
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), collect)
+            .await
+            .expect("stream should complete without [DONE]");
+
+        assert_matches!(
+            &events[..],
+            [
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id, .. }),
+                ResponseEvent::Completed { .. }
+            ] if call_id == "call_a"
+        );
+    }
+
+    #[tokio::test]
+    async fn terminal_tool_calls_finish_keeps_trailing_usage() {
+        let delta = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [{
+                        "id": "call_a",
+                        "function": { "name": "do_a", "arguments": "{}" }
+                    }]
+                }
+            }]
+        });
+        let finish = json!({
+            "choices": [{
+                "finish_reason": "tool_calls"
+            }]
+        });
+        let usage = json!({
+            "choices": [],
+            "usage": {
+                "prompt_tokens": 3,
+                "completion_tokens": 2,
+                "total_tokens": 5
+            }
+        });
+
+        let body = build_body(&[delta, finish, usage]);
+        let reader = ReaderStream::new(std::io::Cursor::new(body))
+            .map_err(|err| codex_client::TransportError::Network(err.to_string()))
+            .chain(futures::stream::pending());
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
+        tokio::spawn(process_chat_sse_with_format(
+            reader,
+            tx,
+            Duration::from_secs(60),
+            None,
+            ChatReasoningFormat::Standard,
+            ChatStreamOptions {
+                tool_calls_finish_terminal: true,
+                ..Default::default()
+            },
+            /*cancellation*/ None,
+        ));
+
+        let mut events = Vec::new();
+        let collect = async {
+            while let Some(ev) = rx.recv().await {
+                events.push(ev.expect("stream error"));
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), collect)
+            .await
+            .expect("stream should complete without [DONE]");
+
+        assert_matches!(
+            &events[..],
+            [
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { .. }),
+                ResponseEvent::Completed {
+                    token_usage: Some(TokenUsage {
+                        total_tokens: 5,
+                        ..
+                    }),
+                    ..
+                }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn cancellation_flushes_partial_assistant_message() {
+        let delta = json!({
+            "choices": [{
+                "delta": { "content": "partial answer" }
+            }]
+        });
+        let body = build_body(&[delta]);
+        let reader = ReaderStream::new(std::io::Cursor::new(body))
+            .map_err(|err| codex_client::TransportError::Network(err.to_string()))
+            .chain(futures::stream::pending());
+        let cancellation = CancellationToken::new();
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
+        tokio::spawn(process_chat_sse_with_format(
+            reader,
+            tx,
+            Duration::from_secs(60),
+            None,
+            ChatReasoningFormat::Standard,
+            ChatStreamOptions::default(),
+            Some(cancellation.clone()),
+        ));
+
+        let mut events = Vec::new();
+        let collect = async {
+            while let Some(ev) = rx.recv().await {
+                let ev = ev.expect("stream error");
+                if matches!(ev, ResponseEvent::OutputTextDelta(_)) {
+                    cancellation.cancel();
+                }
+                events.push(ev);
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), collect)
+            .await
+            .expect("cancelled stream should end");
+
+        assert_matches!(
+            &events[..],
+            [
+                ResponseEvent::OutputItemAdded(_),
+                ResponseEvent::OutputTextDelta(_),
+                ResponseEvent::OutputItemDone(ResponseItem::Message { role, .. }),
+            ] if role == "assistant"
+        );
+        assert_eq!(
+            assistant_output_text_parts(&events),
+            vec![vec!["partial answer".to_string()]]
+        );
+    }
+
+    #[tokio::test]
+    async fn repairs_malformed_tool_call_arguments_only_when_enabled() {
+        let tool_call = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [{
+                        "index": 0,
+                        "id": "call_a",
+                        "function": { "name": "do_a", "arguments": "{\"foo\": 1,}" }
+                    }]
+                }
+            }]
+        });
+        let finish = json!({
+            "choices": [{
+                "finish_reason": "tool_calls"
+            }]
+        });
+        let body = build_body(&[tool_call, finish]);
+        let arguments = |events: Vec<ResponseEvent>| -> Vec<String> {
+            events
+                .into_iter()
+                .filter_map(|ev| match ev {
+                    ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                        arguments, ..
+                    }) => Some(arguments),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let lenient = ChatStreamOptions {
+            repair_tool_call_arguments: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            arguments(
+                collect_events_with_options(&body, ChatReasoningFormat::Standard, lenient).await
+            ),
+            vec!["{\"foo\": 1}".to_string()]
+        );
+        assert_eq!(
+            arguments(collect_events(&body).await),
+            vec!["{\"foo\": 1,}".to_string()]
+        );
+    }
 }
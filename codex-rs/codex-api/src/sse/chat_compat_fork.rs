@@ -1,8 +1,65 @@
+use crate::provider::Provider;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ChatReasoningFormat {
     Standard,
-    #[allow(dead_code)]
-    MinimaxThinkTags,
+    /// Reasoning is inlined in assistant content between the given tags and
+    /// split out into reasoning deltas.
+    ThinkTags(ThinkTagConfig),
+}
+
+/// Delimiters around inline reasoning in assistant content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ThinkTagConfig {
+    pub(crate) open: &'static str,
+    pub(crate) close: &'static str,
+}
+
+impl ThinkTagConfig {
+    /// `<think>...</think>`.
+    pub(crate) const THINK: Self = Self {
+        open: "<think>",
+        close: "</think>",
+    };
+    /// `<thinking>...</thinking>`, as emitted by DeepSeek.
+    pub(crate) const THINKING: Self = Self {
+        open: "<thinking>",
+        close: "</thinking>",
+    };
+    /// `<reasoning>...</reasoning>`.
+    pub(crate) const REASONING: Self = Self {
+        open: "<reasoning>",
+        close: "</reasoning>",
+    };
+}
+
+/// Provider-specific switches for the Chat Completions stream parser.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ChatStreamOptions {
+    /// Treat `finish_reason: "tool_calls"` as the end of the stream and send
+    /// `Completed` once a trailing usage chunk arrives or a short grace period
+    /// passes, for providers that never follow it with `[DONE]`.
+    pub(crate) tool_calls_finish_terminal: bool,
+    /// Replace provider tool-call ids with synthetic ones that are unique
+    /// across streams, for providers whose ids are empty or reused.
+    pub(crate) synthesize_tool_call_ids: bool,
+    /// Attempt to repair tool-call arguments that are not valid JSON before
+    /// emitting the call; see [`repair_tool_call_arguments`].
+    pub(crate) repair_tool_call_arguments: bool,
+}
+
+impl ChatStreamOptions {
+    pub(crate) fn for_provider(provider: &Provider) -> Self {
+        Self {
+            tool_calls_finish_terminal: provider.compat.tool_calls_finish_terminal,
+            synthesize_tool_call_ids: provider.compat.synthesize_tool_call_ids,
+            repair_tool_call_arguments: provider.compat.repair_tool_call_arguments,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -36,7 +93,7 @@ impl ThinkTagStreamSplitter {
             ChatReasoningFormat::Standard => {
                 vec![ContentSegment::Assistant(text.to_string())]
             }
-            ChatReasoningFormat::MinimaxThinkTags => self.split_minimax_chunk(text),
+            ChatReasoningFormat::ThinkTags(tags) => self.split_minimax_chunk(text, tags),
         }
     }
 
@@ -53,28 +110,30 @@ impl ThinkTagStreamSplitter {
         }
     }
 
-    fn split_minimax_chunk(&mut self, text: &str) -> Vec<ContentSegment> {
-        const OPEN_TAG: &str = "<think>";
-        const CLOSE_TAG: &str = "</think>";
+    fn split_minimax_chunk(&mut self, text: &str, tags: ThinkTagConfig) -> Vec<ContentSegment> {
+        let ThinkTagConfig {
+            open: open_tag,
+            close: close_tag,
+        } = tags;
 
         self.pending.push_str(text);
         let mut segments = Vec::new();
 
         loop {
             if self.in_think_block {
-                if let Some(pos) = self.pending.find(CLOSE_TAG) {
+                if let Some(pos) = self.pending.find(close_tag) {
                     if pos > 0 {
                         segments.push(ContentSegment::Reasoning(take_prefix(
                             &mut self.pending,
                             pos,
                         )));
                     }
-                    self.pending.drain(..CLOSE_TAG.len());
+                    self.pending.drain(..close_tag.len());
                     self.in_think_block = false;
                     continue;
                 }
 
-                let keep = trailing_partial_tag_len(&self.pending, CLOSE_TAG);
+                let keep = trailing_partial_tag_len(&self.pending, close_tag);
                 let emit_len = self.pending.len().saturating_sub(keep);
                 if emit_len > 0 {
                     segments.push(ContentSegment::Reasoning(take_prefix(
@@ -85,19 +144,19 @@ impl ThinkTagStreamSplitter {
                 break;
             }
 
-            if let Some(pos) = self.pending.find(OPEN_TAG) {
+            if let Some(pos) = self.pending.find(open_tag) {
                 if pos > 0 {
                     segments.push(ContentSegment::Assistant(take_prefix(
                         &mut self.pending,
                         pos,
                     )));
                 }
-                self.pending.drain(..OPEN_TAG.len());
+                self.pending.drain(..open_tag.len());
                 self.in_think_block = true;
                 continue;
             }
 
-            let keep = trailing_partial_tag_len(&self.pending, OPEN_TAG);
+            let keep = trailing_partial_tag_len(&self.pending, open_tag);
             let emit_len = self.pending.len().saturating_sub(keep);
             if emit_len > 0 {
                 segments.push(ContentSegment::Assistant(take_prefix(
@@ -131,16 +190,149 @@ fn take_prefix(buffer: &mut String, end: usize) -> String {
     std::mem::replace(buffer, tail)
 }
 
+/// Synthetic tool-call ids for one stream. The prefix combines the stream
+/// start time with a process-wide counter so ids do not collide with calls
+/// from earlier turns (or resumed sessions) in the conversation history.
+pub(crate) struct SyntheticToolCallIds {
+    prefix: String,
+}
+
+impl SyntheticToolCallIds {
+    pub(crate) fn new() -> Self {
+        static STREAM_SEQ: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let seq = STREAM_SEQ.fetch_add(1, Ordering::Relaxed);
+        Self {
+            prefix: format!("call_{nanos:x}{seq:x}"),
+        }
+    }
+
+    pub(crate) fn id_for(&self, index: usize) -> String {
+        format!("{}_{index}", self.prefix)
+    }
+}
+
+/// Repairs common JSON mistakes in streamed tool-call arguments: trailing
+/// commas before `}`/`]` and single-quoted strings. Arguments that already
+/// parse, or that still do not parse after repair, are returned unchanged so
+/// the tool reports the original error.
+pub(crate) fn repair_tool_call_arguments(arguments: String) -> String {
+    if serde_json::from_str::<serde_json::Value>(&arguments).is_ok() {
+        return arguments;
+    }
+    let repaired = repair_json(&arguments);
+    if serde_json::from_str::<serde_json::Value>(&repaired).is_ok() {
+        tracing::debug!("repaired malformed tool-call arguments: {arguments}");
+        repaired
+    } else {
+        arguments
+    }
+}
+
+fn repair_json(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    // Quote character of the string being copied, if any.
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(open) => match c {
+                '\\' => match chars.next() {
+                    // `\'` is not a valid JSON escape.
+                    Some('\'') => out.push('\''),
+                    Some(escaped) => {
+                        out.push('\\');
+                        out.push(escaped);
+                    }
+                    None => out.push('\\'),
+                },
+                '"' if open == '\'' => out.push_str("\\\""),
+                c if c == open => {
+                    out.push('"');
+                    quote = None;
+                }
+                c => out.push(c),
+            },
+            None => match c {
+                '"' | '\'' => {
+                    out.push('"');
+                    quote = Some(c);
+                }
+                ',' => {
+                    let mut rest = chars.clone();
+                    while rest.next_if(|next| next.is_whitespace()).is_some() {}
+                    if !matches!(rest.peek(), Some('}' | ']')) {
+                        out.push(',');
+                    }
+                }
+                c => out.push(c),
+            },
+        }
+    }
+    out
+}
+
+/// Alternate locations where OpenAI-compatible gateways report usage, checked
+/// in order after the standard top-level `usage` key.
+const ALTERNATE_USAGE_PATHS: &[&[&str]] = &[&["response", "usage"], &["x_usage"]];
+
+/// Finds the usage object in a Chat Completions chunk, falling back to the
+/// non-standard locations some gateways use.
+pub(crate) fn find_chat_usage(chunk: &serde_json::Value) -> Option<&serde_json::Value> {
+    chunk.get("usage").or_else(|| {
+        ALTERNATE_USAGE_PATHS
+            .iter()
+            .find_map(|path| path.iter().try_fold(chunk, |value, key| value.get(*key)))
+    })
+}
+
+/// Rewrites a non-streaming Chat Completions response into an equivalent SSE
+/// body (one chunk followed by `[DONE]`) so it can be fed through the same
+/// parser as a streamed response.
+///
+/// Each `choices[].message` becomes `choices[].delta`, and tool calls get an
+/// explicit `index` since the streaming parser keys accumulation on it.
+pub(crate) fn chat_completion_to_sse_body(completion: &serde_json::Value) -> String {
+    let mut chunk = completion.clone();
+    if let Some(choices) = chunk.get_mut("choices").and_then(|c| c.as_array_mut()) {
+        for choice in choices {
+            let Some(choice) = choice.as_object_mut() else {
+                continue;
+            };
+            let Some(mut message) = choice.remove("message") else {
+                continue;
+            };
+            if let Some(tool_calls) = message.get_mut("tool_calls").and_then(|c| c.as_array_mut()) {
+                for (index, tool_call) in tool_calls.iter_mut().enumerate() {
+                    if let Some(tool_call) = tool_call.as_object_mut() {
+                        tool_call
+                            .entry("index")
+                            .or_insert_with(|| serde_json::json!(index));
+                    }
+                }
+            }
+            choice.insert("delta".to_string(), message);
+        }
+    }
+    format!("data: {chunk}\n\ndata: [DONE]\n\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::ChatReasoningFormat;
     use super::ContentSegment;
+    use super::ThinkTagConfig;
     use super::ThinkTagStreamSplitter;
+    use super::repair_tool_call_arguments;
     use pretty_assertions::assert_eq;
 
     #[test]
     fn splits_minimax_think_blocks() {
-        let mut splitter = ThinkTagStreamSplitter::new(ChatReasoningFormat::MinimaxThinkTags);
+        let mut splitter =
+            ThinkTagStreamSplitter::new(ChatReasoningFormat::ThinkTags(ThinkTagConfig::THINK));
         let segments = splitter.split_chunk("a<think>b</think>c");
         assert_eq!(
             segments,
@@ -154,7 +346,8 @@ mod tests {
 
     #[test]
     fn handles_split_tags_across_chunks() {
-        let mut splitter = ThinkTagStreamSplitter::new(ChatReasoningFormat::MinimaxThinkTags);
+        let mut splitter =
+            ThinkTagStreamSplitter::new(ChatReasoningFormat::ThinkTags(ThinkTagConfig::THINK));
         let mut segments = splitter.split_chunk("<th");
         assert_eq!(segments, vec![]);
 
@@ -170,6 +363,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handles_split_thinking_tags_across_chunks() {
+        let mut splitter =
+            ThinkTagStreamSplitter::new(ChatReasoningFormat::ThinkTags(ThinkTagConfig::THINKING));
+        let mut segments = splitter.split_chunk("hi <thin");
+        segments.extend(splitter.split_chunk("king>plan</thi"));
+        segments.extend(splitter.split_chunk("nking> <think>done"));
+        assert_eq!(
+            segments,
+            vec![
+                ContentSegment::Assistant("hi ".to_string()),
+                ContentSegment::Reasoning("plan".to_string()),
+                ContentSegment::Assistant(" <think>done".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn flushes_partial_reasoning_tag_as_content() {
+        let mut splitter =
+            ThinkTagStreamSplitter::new(ChatReasoningFormat::ThinkTags(ThinkTagConfig::REASONING));
+        assert_eq!(
+            splitter.split_chunk("a<reaso"),
+            vec![ContentSegment::Assistant("a".to_string())]
+        );
+        assert_eq!(
+            splitter.flush_remaining(),
+            vec![ContentSegment::Assistant("<reaso".to_string())]
+        );
+    }
+
     #[test]
     fn standard_mode_passthrough() {
         let mut splitter = ThinkTagStreamSplitter::new(ChatReasoningFormat::Standard);
@@ -178,4 +402,24 @@ mod tests {
             vec![ContentSegment::Assistant("<think>x</think>".to_string())]
         );
     }
+
+    #[test]
+    fn repairs_trailing_commas_and_single_quotes() {
+        assert_eq!(
+            repair_tool_call_arguments(r#"{"paths": ["a", "b",], "recursive": true,}"#.to_string()),
+            r#"{"paths": ["a", "b"], "recursive": true}"#
+        );
+        assert_eq!(
+            repair_tool_call_arguments(r#"{'command': 'say "hi"', 'note': 'it\'s'}"#.to_string()),
+            r#"{"command": "say \"hi\"", "note": "it's"}"#
+        );
+    }
+
+    #[test]
+    fn leaves_valid_and_unrepairable_arguments_unchanged() {
+        let valid = r#"{"text": "a, }"}"#.to_string();
+        assert_eq!(repair_tool_call_arguments(valid.clone()), valid);
+        let truncated = r#"{"path": "src/ma"#.to_string();
+        assert_eq!(repair_tool_call_arguments(truncated.clone()), truncated);
+    }
 }
@@ -144,6 +144,7 @@ fn provider(name: &str) -> Provider {
         },
         stream_idle_timeout: Duration::from_millis(10),
         system_role: None,
+        compat: Default::default(),
     }
 }
 
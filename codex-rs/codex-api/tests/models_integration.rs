@@ -43,6 +43,7 @@ fn provider(base_url: &str) -> Provider {
         },
         stream_idle_timeout: std::time::Duration::from_secs(1),
         system_role: None,
+        compat: Default::default(),
     }
 }
 
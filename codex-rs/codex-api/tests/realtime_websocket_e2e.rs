@@ -72,6 +72,7 @@ fn test_provider(base_url: String) -> Provider {
         },
         stream_idle_timeout: Duration::from_secs(5),
         system_role: None,
+        compat: Default::default(),
     }
 }
 
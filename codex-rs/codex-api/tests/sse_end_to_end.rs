@@ -5,6 +5,7 @@ use std::time::Duration;
 use anyhow::Result;
 use bytes::Bytes;
 use codex_api::AuthProvider;
+use codex_api::ChatCompatClient;
 use codex_api::Compression;
 use codex_api::Provider;
 use codex_api::ResponseEvent;
@@ -14,12 +15,14 @@ use codex_client::Request;
 use codex_client::Response;
 use codex_client::StreamResponse;
 use codex_client::TransportError;
+use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 use futures::StreamExt;
 use http::HeaderMap;
 use http::StatusCode;
 use pretty_assertions::assert_eq;
 use serde_json::Value;
+use tokio_util::sync::CancellationToken;
 
 #[derive(Clone)]
 struct FixtureSseTransport {
@@ -49,6 +52,30 @@ impl HttpTransport for FixtureSseTransport {
     }
 }
 
+/// Streams `body` and then keeps the connection open without sending more.
+#[derive(Clone)]
+struct OpenSseTransport {
+    body: String,
+}
+
+impl HttpTransport for OpenSseTransport {
+    async fn execute(&self, _req: Request) -> Result<Response, TransportError> {
+        Err(TransportError::Build("execute should not run".to_string()))
+    }
+
+    async fn stream(&self, _req: Request) -> Result<StreamResponse, TransportError> {
+        let stream = futures::stream::iter(vec![Ok::<Bytes, TransportError>(Bytes::from(
+            self.body.clone(),
+        ))])
+        .chain(futures::stream::pending());
+        Ok(StreamResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            bytes: Box::pin(stream),
+        })
+    }
+}
+
 #[derive(Clone, Default)]
 struct NoAuth;
 
@@ -71,6 +98,7 @@ fn provider(name: &str) -> Provider {
         },
         stream_idle_timeout: Duration::from_millis(50),
         system_role: None,
+        compat: Default::default(),
     }
 }
 
@@ -169,3 +197,125 @@ async fn responses_stream_parses_items_and_completed_end_to_end() -> Result<()>
 
     Ok(())
 }
+
+#[tokio::test]
+async fn chat_stream_flushes_partial_message_when_cancelled() -> Result<()> {
+    let delta = serde_json::json!({
+        "choices": [{
+            "delta": { "content": "partial answer" }
+        }]
+    });
+    let transport = OpenSseTransport {
+        body: format!("data: {delta}\n\n"),
+    };
+    let provider = Provider {
+        stream_idle_timeout: Duration::from_secs(60),
+        ..provider("MiniMax")
+    };
+    let cancellation = CancellationToken::new();
+    let client = ChatCompatClient::new(transport, provider, Arc::new(NoAuth))
+        .with_cancellation(cancellation.clone());
+
+    let mut stream = client
+        .stream_prompt(
+            "MiniMax-M2.5",
+            "",
+            &[],
+            &[],
+            /*effort*/ None,
+            /*conversation_id*/ None,
+            /*session_source*/ None,
+        )
+        .await?;
+
+    let mut events = Vec::new();
+    let collect = async {
+        while let Some(ev) = stream.next().await {
+            let ev = ev?;
+            if matches!(ev, ResponseEvent::OutputTextDelta(_)) {
+                cancellation.cancel();
+            }
+            events.push(ev);
+        }
+        Ok::<(), anyhow::Error>(())
+    };
+    tokio::time::timeout(Duration::from_secs(5), collect)
+        .await
+        .expect("cancelled stream should end")?;
+
+    let Some(ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. })) =
+        events.last()
+    else {
+        panic!("expected the partial message to be flushed, got {events:?}");
+    };
+    assert_eq!(role, "assistant");
+    assert_eq!(
+        content,
+        &vec![ContentItem::OutputText {
+            text: "partial answer".to_string()
+        }]
+    );
+    assert!(
+        !events
+            .iter()
+            .any(|ev| matches!(ev, ResponseEvent::Completed { .. })),
+        "cancelled stream should not complete: {events:?}"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn chat_stream_reports_provider_id_before_completion() -> Result<()> {
+    let delta = serde_json::json!({
+        "choices": [{
+            "delta": { "content": "hi" }
+        }]
+    });
+    let finish = serde_json::json!({
+        "choices": [{
+            "delta": {},
+            "finish_reason": "stop"
+        }]
+    });
+    let transport = FixtureSseTransport::new(format!(
+        "data: {delta}\n\ndata: {finish}\n\ndata: [DONE]\n\n"
+    ));
+    let client = ChatCompatClient::new(transport, provider("MiniMax"), Arc::new(NoAuth))
+        .with_provider_id("minimax".to_string());
+
+    let mut stream = client
+        .stream_prompt(
+            "MiniMax-M2.5",
+            "",
+            &[],
+            &[],
+            /*effort*/ None,
+            /*conversation_id*/ None,
+            /*session_source*/ None,
+        )
+        .await?;
+
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(ev?);
+    }
+
+    let served_by: Vec<(String, String)> = events
+        .iter()
+        .filter_map(|ev| match ev {
+            ResponseEvent::ServedBy { provider, model } => Some((provider.clone(), model.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        served_by,
+        vec![("minimax".to_string(), "MiniMax-M2.5".to_string())]
+    );
+    assert!(
+        matches!(events.last(), Some(ResponseEvent::Completed { .. })),
+        "{events:?}"
+    );
+
+    Ok(())
+}
@@ -71,3 +71,72 @@ where
     }
     Err(TransportError::RetryLimit)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::Method;
+    use http::StatusCode;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+
+    fn policy(retry_429: bool) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(1),
+            retry_on: RetryOn {
+                retry_429,
+                retry_5xx: true,
+                retry_transport: true,
+            },
+        }
+    }
+
+    async fn attempts_until_error(policy: RetryPolicy, status: StatusCode) -> u64 {
+        let attempts = AtomicU64::new(0);
+        let result: Result<(), TransportError> = run_with_retry(
+            policy,
+            || {
+                Request::new(
+                    Method::POST,
+                    "https://example.com/v1/chat/completions".into(),
+                )
+            },
+            |_req, _attempt| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    Err(TransportError::Http {
+                        status,
+                        url: None,
+                        headers: None,
+                        body: None,
+                    })
+                }
+            },
+        )
+        .await;
+        assert!(matches!(result, Err(TransportError::Http { status: s, .. }) if s == status));
+        attempts.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn bad_request_fails_without_retry() {
+        assert_eq!(
+            attempts_until_error(policy(/*retry_429*/ true), StatusCode::BAD_REQUEST).await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn too_many_requests_retries_up_to_limit_when_enabled() {
+        assert_eq!(
+            attempts_until_error(policy(/*retry_429*/ true), StatusCode::TOO_MANY_REQUESTS).await,
+            5
+        );
+        assert_eq!(
+            attempts_until_error(policy(/*retry_429*/ false), StatusCode::TOO_MANY_REQUESTS).await,
+            1
+        );
+    }
+}
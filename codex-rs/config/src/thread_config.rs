@@ -317,6 +317,7 @@ mod tests {
             requires_openai_auth: false,
             supports_websockets: true,
             system_role: None,
+            compat: None,
         }
     }
 }
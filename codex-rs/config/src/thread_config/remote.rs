@@ -191,6 +191,7 @@ fn model_provider_from_proto(
         requires_openai_auth: provider.requires_openai_auth,
         supports_websockets: provider.supports_websockets,
         system_role: None,
+        compat: None,
     };
     Ok((id, info))
 }
@@ -219,6 +220,7 @@ fn model_provider_to_proto(
         requires_openai_auth,
         supports_websockets,
         system_role: _,
+        compat: _,
     } = provider;
 
     proto::ModelProvider {
@@ -540,6 +542,7 @@ mod tests {
             requires_openai_auth: false,
             supports_websockets: true,
             system_role: None,
+            compat: None,
             aws: None,
         }
     }
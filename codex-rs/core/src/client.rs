@@ -281,6 +281,12 @@ pub struct ModelClientSession {
     /// keep sending it unchanged between turn requests (e.g., for retries, incremental
     /// appends, or continuation requests), and must not send it between different turns.
     turn_state: Arc<OnceLock<String>>,
+    /// Fork: turn cancellation passed to Chat Completions streams, which flush
+    /// the partial output received so far when it fires.
+    chat_cancellation: Option<CancellationToken>,
+    /// Fork: `model_providers` id reported when a Chat Completions response
+    /// is served.
+    chat_provider_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -480,6 +486,8 @@ impl ModelClient {
             client: self.clone(),
             websocket_session: self.take_cached_websocket_session(),
             turn_state: Arc::new(OnceLock::new()),
+            chat_cancellation: None,
+            chat_provider_id: None,
         }
     }
 
@@ -1098,6 +1106,17 @@ impl ModelClientSession {
         Arc::clone(&self.turn_state)
     }
 
+    /// Fork: cancels Chat Completions streams started by this session when
+    /// `cancellation` fires.
+    pub(crate) fn set_chat_cancellation(&mut self, cancellation: CancellationToken) {
+        self.chat_cancellation = Some(cancellation);
+    }
+
+    /// Fork: sets the provider id reported for Chat Completions responses.
+    pub(crate) fn set_chat_provider_id(&mut self, provider_id: String) {
+        self.chat_provider_id = Some(provider_id);
+    }
+
     fn reset_websocket_session(&mut self) {
         self.websocket_session.connection = None;
         self.websocket_session.last_request = None;
@@ -1488,6 +1507,7 @@ impl ModelClientSession {
         prompt: &Prompt,
         model_info: &ModelInfo,
         session_telemetry: &SessionTelemetry,
+        effort: Option<ReasoningEffortConfig>,
     ) -> Result<ResponseStream> {
         if prompt.output_schema.is_some() {
             return Err(CodexErr::UnsupportedOperation(
@@ -1520,6 +1540,9 @@ impl ModelClientSession {
             .collect::<Vec<_>>();
         let conversation_id = self.client.state.thread_id.to_string();
         let session_source = self.client.state.session_source.clone();
+        let effort = effort
+            .or_else(|| model_info.default_reasoning_level.clone())
+            .map(reasoning_effort_for_request);
 
         let mut auth_recovery = auth_manager
             .as_ref()
@@ -1541,12 +1564,18 @@ impl ModelClientSession {
                 RequestRouteTelemetry::for_endpoint(CHAT_COMPLETIONS_ENDPOINT),
                 self.client.state.auth_env_telemetry.clone(),
             );
-            let client = ApiChatCompatClient::new(
+            let mut client = ApiChatCompatClient::new(
                 transport,
                 client_setup.api_provider,
                 client_setup.api_auth,
             )
             .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
+            if let Some(cancellation) = &self.chat_cancellation {
+                client = client.with_cancellation(cancellation.clone());
+            }
+            if let Some(provider_id) = &self.chat_provider_id {
+                client = client.with_provider_id(provider_id.clone());
+            }
 
             let stream_result = client
                 .stream_prompt(
@@ -1554,6 +1583,7 @@ impl ModelClientSession {
                     &instructions,
                     &input,
                     &tools_json,
+                    effort.as_ref(),
                     Some(conversation_id.clone()),
                     Some(session_source.clone()),
                 )
@@ -1905,7 +1935,7 @@ impl ModelClientSession {
                 .await
             }
             WireApi::Chat => {
-                self.stream_chat_completions(prompt, model_info, session_telemetry)
+                self.stream_chat_completions(prompt, model_info, session_telemetry, effort)
                     .await
             } // Fork: chat-api
         }
@@ -102,6 +102,7 @@ use codex_protocol::protocol::PlanDeltaEvent;
 use codex_protocol::protocol::ReasoningContentDeltaEvent;
 use codex_protocol::protocol::ReasoningRawContentDeltaEvent;
 use codex_protocol::protocol::SafetyBufferingEvent;
+use codex_protocol::protocol::ServedByEvent;
 use codex_protocol::protocol::TurnDiffEvent;
 use codex_protocol::protocol::WarningEvent;
 use codex_protocol::user_input::UserInput;
@@ -1504,6 +1505,7 @@ pub(super) fn realtime_text_for_event(msg: &EventMsg) -> Option<(String, Option<
         | EventMsg::ModelVerification(_)
         | EventMsg::TurnModerationMetadata(_)
         | EventMsg::SafetyBuffering(_)
+        | EventMsg::ServedBy(_)
         | EventMsg::ContextCompacted(_)
         | EventMsg::ThreadRolledBack(_)
         | EventMsg::TurnStarted(_)
@@ -1909,6 +1911,10 @@ async fn try_run_sampling_request(
         turn_context.provider.info().name.as_str(),
     );
     let sampling_timing_guard = turn_context.turn_timing_state.begin_sampling();
+    // Fork: let Chat Completions streams flush partial output on interrupt
+    // and report the provider id that served them.
+    client_session.set_chat_cancellation(cancellation_token.clone());
+    client_session.set_chat_provider_id(turn_context.config.model_provider_id.clone());
     let mut stream = client_session
         .stream(
             prompt,
@@ -2199,6 +2205,20 @@ async fn try_run_sampling_request(
             ResponseEvent::ServerReasoningIncluded(included) => {
                 sess.set_server_reasoning_included(included).await;
             }
+            ResponseEvent::ServedBy { provider, model } => {
+                // Fork: attribute the turn to the provider that served it.
+                tracing::info!(
+                    turn_id = %turn_context.sub_id,
+                    %provider,
+                    %model,
+                    "chat completions response served"
+                );
+                sess.send_event(
+                    &turn_context,
+                    EventMsg::ServedBy(ServedByEvent { provider, model }),
+                )
+                .await;
+            }
             ResponseEvent::RateLimits(snapshot) => {
                 // Update internal state with latest rate limits, but defer sending until
                 // token usage is available to avoid duplicate TokenCount events.
@@ -1,4 +1,7 @@
 use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::PoisonError;
 
 use crate::apply_patch;
 use crate::apply_patch::InternalApplyPatchInvocation;
@@ -19,20 +22,53 @@ use crate::tools::registry::ToolExecutor;
 use crate::tools::runtimes::apply_patch::ApplyPatchRequest;
 use crate::tools::runtimes::apply_patch::ApplyPatchRuntime;
 use crate::tools::sandboxing::ToolCtx;
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchError;
+use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::ParseError;
+use codex_exec_server::ExecutorFileSystem;
+use codex_exec_server::FileSystemSandboxContext;
 use codex_tools::JsonSchema;
+use codex_tools::JsonSchemaPrimitiveType;
+use codex_tools::JsonSchemaType;
 use codex_tools::ResponsesApiTool;
 use codex_tools::ToolName;
 use codex_tools::ToolSpec;
+use codex_utils_path_uri::PathUri;
 use serde::Deserialize;
 
-pub struct StructuredEditHandler;
+pub struct StructuredEditHandler {
+    strict_schema: bool,
+}
+
+impl StructuredEditHandler {
+    /// `strict_schema` selects the strict-compatible tool schema for
+    /// providers that only call tools reliably with `strict: true`.
+    pub fn new(strict_schema: bool) -> Self {
+        Self { strict_schema }
+    }
+}
+
+pub(crate) fn create_text_editor_tool(strict: bool) -> ToolSpec {
+    // Strict schemas require every property in `required`, so parameters that
+    // only some commands use are made nullable instead of optional.
+    let optional = |mut schema: JsonSchema| {
+        if strict && let Some(JsonSchemaType::Single(primitive)) = schema.schema_type.take() {
+            schema.schema_type = Some(JsonSchemaType::Multiple(vec![
+                primitive,
+                JsonSchemaPrimitiveType::Null,
+            ]));
+        }
+        schema
+    };
+    let optional_string =
+        |description: &str| optional(JsonSchema::string(Some(description.to_string())));
 
-pub(crate) fn create_text_editor_tool() -> ToolSpec {
     let mut properties = BTreeMap::new();
     properties.insert(
         "command".to_string(),
         JsonSchema::string(Some(
-            "The editing command to execute. One of: 'create', 'str_replace', 'delete'."
+            "The editing command to execute. One of: 'create', 'str_replace', 'str_replace_all', 'apply_diff', 'delete', 'undo'."
                 .to_string(),
         )),
     );
@@ -42,33 +78,52 @@ pub(crate) fn create_text_editor_tool() -> ToolSpec {
     );
     properties.insert(
         "file_text".to_string(),
-        JsonSchema::string(Some(
-            "Required for 'create' command. The full content of the new file.".to_string(),
-        )),
+        optional_string("Required for 'create' command. The full content of the new file."),
     );
     properties.insert(
-        "old_str".to_string(),
-        JsonSchema::string(Some(
-            "Required for 'str_replace' command. The exact text to find in the file. Must match exactly once."
+        "overwrite".to_string(),
+        optional(JsonSchema::boolean(Some(
+            "Optional for 'create' command. Set to true to replace an existing file at 'path'."
                 .to_string(),
-        )),
+        ))),
+    );
+    properties.insert(
+        "old_str".to_string(),
+        optional_string(
+            "Required for 'str_replace' and 'str_replace_all' commands. The exact text to find in the file. Must match exactly once for 'str_replace'.",
+        ),
     );
     properties.insert(
         "new_str".to_string(),
-        JsonSchema::string(Some(
-            "Required for 'str_replace' command. The replacement text. Omit or set empty to delete old_str."
-                .to_string(),
-        )),
+        optional_string(
+            "Required for 'str_replace' and 'str_replace_all' commands. The replacement text. Omit or set empty to delete old_str.",
+        ),
     );
 
+    properties.insert(
+        "diff".to_string(),
+        optional_string(
+            "Required for 'apply_diff' command. A unified diff (with @@ hunk headers) against the current contents of 'path'.",
+        ),
+    );
+
+    let required = if strict {
+        properties.keys().cloned().collect()
+    } else {
+        vec!["command".to_string(), "path".to_string()]
+    };
+
     ToolSpec::Function(ResponsesApiTool {
         name: "text_editor".to_string(),
         description: r#"Edit files using structured commands.
 
 Commands:
-- **create**: Create a new file. Requires 'path' and 'file_text'.
+- **create**: Create a new file. Requires 'path' and 'file_text'. Fails if the file exists unless 'overwrite' is true.
 - **str_replace**: Replace text in an existing file. Requires 'path', 'old_str', and 'new_str'. The 'old_str' must match exactly one location in the file.
+- **str_replace_all**: Replace every occurrence of 'old_str' in an existing file, e.g. to rename an identifier. Requires 'path', 'old_str', and 'new_str'.
+- **apply_diff**: Apply a unified diff to an existing file. Requires 'path' and 'diff'. The diff must cover only 'path' and its context lines must match the file exactly.
 - **delete**: Delete a file. Requires 'path'.
+- **undo**: Revert the most recent text_editor edit to 'path' made in this session. Requires 'path'. Earlier edits can be undone by repeating it, within a bounded history. Returns the restored content, truncated for large files.
 
 Examples:
 
@@ -82,13 +137,9 @@ Delete a file:
   {"command": "delete", "path": "obsolete.txt"}
 "#
         .to_string(),
-        strict: false,
+        strict,
         defer_loading: None,
-        parameters: JsonSchema::object(
-            properties,
-            Some(vec!["command".to_string(), "path".to_string()]),
-            Some(false.into()),
-        ),
+        parameters: JsonSchema::object(properties, Some(required), Some(false.into())),
         output_schema: None,
     })
 }
@@ -103,18 +154,150 @@ struct StructuredEditArgs {
     old_str: Option<String>,
     #[serde(default)]
     new_str: Option<String>,
+    #[serde(default)]
+    overwrite: Option<bool>,
+    #[serde(default)]
+    diff: Option<String>,
 }
 
 /// Number of context lines to include before and after a change in generated patches.
 const CONTEXT_LINES: usize = 3;
 
+/// Most prior versions of one file kept for `undo`; older ones are dropped.
+const MAX_UNDO_ENTRIES_PER_FILE: usize = 16;
+
+/// Total bytes of prior file contents kept for `undo` across the session.
+/// The oldest entries are dropped first once the limit is exceeded.
+const MAX_UNDO_HISTORY_BYTES: usize = 8 * 1024 * 1024;
+
+/// Most lines of the restored file echoed back by `undo`.
+const MAX_UNDO_OUTPUT_LINES: usize = 200;
+
+/// Most bytes of the restored file echoed back by `undo`.
+const MAX_UNDO_OUTPUT_BYTES: usize = 16 * 1024;
+
+/// Session-scoped record of file contents before each `text_editor` edit,
+/// consumed by the `undo` command. Stored in the session's extension data so
+/// it survives across turns but not across sessions. Bounded by
+/// [`MAX_UNDO_ENTRIES_PER_FILE`] and [`MAX_UNDO_HISTORY_BYTES`].
+#[derive(Default)]
+struct StructuredEditHistory {
+    edits: Mutex<EditStacks>,
+}
+
+#[derive(Default)]
+struct EditStacks {
+    /// Per-file stack of prior contents, oldest first.
+    by_path: HashMap<PathUri, Vec<RecordedEdit>>,
+    /// Sum of the retained prior contents' lengths.
+    bytes: usize,
+    next_seq: u64,
+}
+
+struct RecordedEdit {
+    /// Recording order across all files, used to evict the oldest entry.
+    seq: u64,
+    /// `None` means the file did not exist.
+    previous: Option<String>,
+}
+
+impl RecordedEdit {
+    fn len(&self) -> usize {
+        self.previous.as_ref().map_or(0, String::len)
+    }
+}
+
+impl StructuredEditHistory {
+    fn record(&self, path: PathUri, previous: Option<String>) {
+        let mut edits = self.edits.lock().unwrap_or_else(PoisonError::into_inner);
+        let edit = RecordedEdit {
+            seq: edits.next_seq,
+            previous,
+        };
+        edits.next_seq += 1;
+        if edit.len() > MAX_UNDO_HISTORY_BYTES {
+            // Too large to keep. Forget the file's older versions too, so a
+            // later undo cannot skip over this edit.
+            if let Some(stack) = edits.by_path.remove(&path) {
+                edits.bytes -= stack.iter().map(RecordedEdit::len).sum::<usize>();
+            }
+            return;
+        }
+        edits.bytes += edit.len();
+        let stack = edits.by_path.entry(path).or_default();
+        stack.push(edit);
+        let excess = stack.len().saturating_sub(MAX_UNDO_ENTRIES_PER_FILE);
+        let dropped: usize = stack.drain(..excess).map(|edit| edit.len()).sum();
+        edits.bytes -= dropped;
+        edits.evict_oldest_over_limit();
+    }
+
+    /// Returns the content `path` had before its most recent edit, without
+    /// removing it, so a failed undo can be retried.
+    fn previous_content(
+        &self,
+        path: &PathUri,
+        display_path: &str,
+    ) -> Result<Option<String>, FunctionCallError> {
+        self.edits
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_path
+            .get(path)
+            .and_then(|stack| stack.last())
+            .map(|edit| edit.previous.clone())
+            .ok_or_else(|| {
+                FunctionCallError::RespondToModel(format!(
+                    "no text_editor edit to undo for {display_path} in this session"
+                ))
+            })
+    }
+
+    fn pop(&self, path: &PathUri) -> Option<Option<String>> {
+        let mut edits = self.edits.lock().unwrap_or_else(PoisonError::into_inner);
+        let stack = edits.by_path.get_mut(path)?;
+        let edit = stack.pop()?;
+        if stack.is_empty() {
+            edits.by_path.remove(path);
+        }
+        edits.bytes -= edit.len();
+        Some(edit.previous)
+    }
+}
+
+impl EditStacks {
+    /// Drops the oldest recorded versions until the byte limit is met. Only
+    /// the bottom of a stack is dropped, so undo never skips an edit.
+    fn evict_oldest_over_limit(&mut self) {
+        while self.bytes > MAX_UNDO_HISTORY_BYTES {
+            let Some(path) = self
+                .by_path
+                .iter()
+                .filter_map(|(path, stack)| stack.first().map(|edit| (edit.seq, path)))
+                .min_by_key(|(seq, _)| *seq)
+                .map(|(_, path)| path.clone())
+            else {
+                return;
+            };
+            let Some(stack) = self.by_path.get_mut(&path) else {
+                return;
+            };
+            let edit = stack.remove(0);
+            if stack.is_empty() {
+                self.by_path.remove(&path);
+            }
+            self.bytes -= edit.len();
+        }
+    }
+}
+
 impl ToolExecutor<ToolInvocation> for StructuredEditHandler {
     fn tool_name(&self) -> ToolName {
         ToolName::plain("text_editor")
     }
 
     fn spec(&self) -> ToolSpec {
-        create_text_editor_tool()
+        create_text_editor_tool(self.strict_schema)
     }
 
     fn handle(&self, invocation: ToolInvocation) -> codex_tools::ToolExecutorFuture<'_> {
@@ -156,6 +339,26 @@ impl StructuredEditHandler {
         let sandbox =
             turn.file_system_sandbox_context(/*additional_permissions*/ None, &cwd_uri);
 
+        let file_path = cwd_uri.join(&args.path).map_err(|e| {
+            FunctionCallError::RespondToModel(format!(
+                "invalid text_editor path '{}': {e}",
+                args.path
+            ))
+        })?;
+        // `Ok(None)` when the file does not exist yet (e.g. before `create`).
+        // Other read failures only matter to commands that need the content.
+        let current_content: Result<Option<String>, String> =
+            match fs.read_file_text(&file_path, Some(&sandbox)).await {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(format!("failed to read file '{}': {e}", args.path)),
+            };
+        let history = session
+            .services
+            .session_extension_data
+            .get_or_init(StructuredEditHistory::default);
+
+        let is_undo = args.command == "undo";
         let patch_string = match args.command.as_str() {
             "create" => {
                 let file_text = args.file_text.ok_or_else(|| {
@@ -163,155 +366,224 @@ impl StructuredEditHandler {
                         "create command requires 'file_text' parameter".to_string(),
                     )
                 })?;
-                generate_create_patch(&args.path, &file_text)
+                let existing = current_content
+                    .clone()
+                    .map_err(FunctionCallError::RespondToModel)?;
+                generate_create_or_overwrite_patch(
+                    &args.path,
+                    &file_text,
+                    existing.as_deref(),
+                    args.overwrite.unwrap_or(false),
+                )?
             }
-            "str_replace" => {
+            command @ ("str_replace" | "str_replace_all") => {
                 let old_str = args.old_str.ok_or_else(|| {
-                    FunctionCallError::RespondToModel(
-                        "str_replace command requires 'old_str' parameter".to_string(),
-                    )
-                })?;
-                let new_str = args.new_str.unwrap_or_default();
-                let file_path = cwd_uri.join(&args.path).map_err(|e| {
                     FunctionCallError::RespondToModel(format!(
-                        "invalid text_editor path '{}': {e}",
-                        args.path
+                        "{command} command requires 'old_str' parameter"
                     ))
                 })?;
-                let file_content = fs
-                    .read_file_text(&file_path, Some(&sandbox))
-                    .await
-                    .map_err(|e| {
+                let new_str = args.new_str.unwrap_or_default();
+                let file_content = current_content
+                    .clone()
+                    .map_err(FunctionCallError::RespondToModel)?
+                    .ok_or_else(|| {
                         FunctionCallError::RespondToModel(format!(
-                            "failed to read file '{}': {e}",
+                            "failed to read file '{}': file does not exist",
                             args.path
                         ))
                     })?;
-                generate_str_replace_patch(&args.path, &old_str, &new_str, &file_content)?
+                if command == "str_replace_all" {
+                    generate_str_replace_all_patch(&args.path, &old_str, &new_str, &file_content)?
+                } else {
+                    generate_str_replace_patch(&args.path, &old_str, &new_str, &file_content)?
+                }
+            }
+            "apply_diff" => {
+                let diff = args.diff.ok_or_else(|| {
+                    FunctionCallError::RespondToModel(
+                        "apply_diff command requires 'diff' parameter".to_string(),
+                    )
+                })?;
+                let file_content = current_content
+                    .clone()
+                    .map_err(FunctionCallError::RespondToModel)?
+                    .ok_or_else(|| {
+                        FunctionCallError::RespondToModel(format!(
+                            "failed to read file '{}': file does not exist; use create for new files",
+                            args.path
+                        ))
+                    })?;
+                generate_apply_diff_patch(&args.path, &diff, &file_content, &cwd_uri)?
             }
             "delete" => generate_delete_patch(&args.path),
+            "undo" => {
+                let previous = history.previous_content(&file_path, &args.path)?;
+                let current = current_content
+                    .clone()
+                    .map_err(FunctionCallError::RespondToModel)?;
+                generate_undo_patch(&args.path, previous.as_deref(), current.as_deref())?
+            }
             other => {
                 return Err(FunctionCallError::RespondToModel(format!(
-                    "unknown command '{other}'. Expected 'create', 'str_replace', or 'delete'"
+                    "unknown command '{other}'. Expected 'create', 'str_replace', 'str_replace_all', 'apply_diff', 'delete', or 'undo'"
                 )));
             }
         };
 
-        // Delegate to the existing apply_patch pipeline.
-        let command = vec!["apply_patch".to_string(), patch_string];
-        match codex_apply_patch::maybe_parse_apply_patch_verified(
-            &command,
+        // Dry-run the generated patch against the current file contents so a
+        // hunk that no longer applies is reported precisely, rather than as a
+        // late failure from the apply pipeline.
+        let action = verify_generated_patch(
+            &args.command,
+            &args.path,
+            patch_string,
             &cwd_uri,
             fs.as_ref(),
             Some(&sandbox),
         )
+        .await?;
+
+        // Delegate to the existing apply_patch pipeline.
+        let (file_paths, effective_additional_permissions, file_system_sandbox_policy) =
+            effective_patch_permissions(
+                session.as_ref(),
+                turn.as_ref(),
+                &turn_environment.environment_id,
+                &action,
+                &cwd_uri,
+            )
+            .await
+            .unwrap_or_else(|_| {
+                crate::tools::handlers::apply_patch::patch_permissions_without_path_matching(
+                    &action,
+                )
+            });
+        let content = match apply_patch::apply_patch(
+            turn.as_ref(),
+            &file_system_sandbox_policy,
+            action,
+        )
         .await
         {
-            codex_apply_patch::MaybeApplyPatchVerified::Body(action) => {
-                let (file_paths, effective_additional_permissions, file_system_sandbox_policy) =
-                    effective_patch_permissions(
-                        session.as_ref(),
+            InternalApplyPatchInvocation::Output(item) => item?,
+            InternalApplyPatchInvocation::DelegateToRuntime(apply) => {
+                let changes = convert_apply_patch_to_protocol(&apply.action);
+                let emitter = ToolEmitter::apply_patch_for_environment(
+                    changes.clone(),
+                    apply.auto_approved,
+                    turn_environment.environment_id.clone(),
+                );
+                let event_ctx =
+                    ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, Some(&tracker));
+                emitter.begin(event_ctx).await;
+
+                let req = ApplyPatchRequest {
+                    turn_environment: turn_environment.clone(),
+                    action: apply.action,
+                    file_paths,
+                    changes,
+                    exec_approval_requirement: apply.exec_approval_requirement,
+                    additional_permissions: effective_additional_permissions.additional_permissions,
+                    permissions_preapproved: effective_additional_permissions
+                        .permissions_preapproved,
+                };
+
+                let mut orchestrator = ToolOrchestrator::new();
+                let mut runtime = ApplyPatchRuntime::new();
+                let tool_ctx = ToolCtx {
+                    session: session.clone(),
+                    turn: turn.clone(),
+                    call_id: call_id.clone(),
+                    tool_name: tool_name.clone(),
+                };
+                let out = orchestrator
+                    .run(
+                        &mut runtime,
+                        &req,
+                        &tool_ctx,
                         turn.as_ref(),
-                        &turn_environment.environment_id,
-                        &action,
-                        &cwd_uri,
+                        turn.approval_policy.value(),
                     )
                     .await
-                    .unwrap_or_else(|_| {
-                        crate::tools::handlers::apply_patch::patch_permissions_without_path_matching(
-                            &action,
-                        )
-                    });
-                match apply_patch::apply_patch(turn.as_ref(), &file_system_sandbox_policy, action)
-                    .await
-                {
-                    InternalApplyPatchInvocation::Output(item) => {
-                        let content = item?;
-                        Ok(boxed_tool_output(FunctionToolOutput::from_text(
-                            content,
-                            Some(true),
-                        )))
-                    }
-                    InternalApplyPatchInvocation::DelegateToRuntime(apply) => {
-                        let changes = convert_apply_patch_to_protocol(&apply.action);
-                        let emitter = ToolEmitter::apply_patch_for_environment(
-                            changes.clone(),
-                            apply.auto_approved,
-                            turn_environment.environment_id.clone(),
-                        );
-                        let event_ctx = ToolEventCtx::new(
-                            session.as_ref(),
-                            turn.as_ref(),
-                            &call_id,
-                            Some(&tracker),
-                        );
-                        emitter.begin(event_ctx).await;
-
-                        let req = ApplyPatchRequest {
-                            turn_environment: turn_environment.clone(),
-                            action: apply.action,
-                            file_paths,
-                            changes,
-                            exec_approval_requirement: apply.exec_approval_requirement,
-                            additional_permissions: effective_additional_permissions
-                                .additional_permissions,
-                            permissions_preapproved: effective_additional_permissions
-                                .permissions_preapproved,
-                        };
-
-                        let mut orchestrator = ToolOrchestrator::new();
-                        let mut runtime = ApplyPatchRuntime::new();
-                        let tool_ctx = ToolCtx {
-                            session: session.clone(),
-                            turn: turn.clone(),
-                            call_id: call_id.clone(),
-                            tool_name: tool_name.clone(),
-                        };
-                        let out = orchestrator
-                            .run(
-                                &mut runtime,
-                                &req,
-                                &tool_ctx,
-                                turn.as_ref(),
-                                turn.approval_policy.value(),
-                            )
-                            .await
-                            .map(|result| result.output);
-                        let (out, delta) = match out {
-                            Ok(output) => (Ok(output.exec_output), Some(output.delta)),
-                            Err(error) => (Err(error), Some(runtime.committed_delta().clone())),
-                        };
-                        let event_ctx = ToolEventCtx::new(
-                            session.as_ref(),
-                            turn.as_ref(),
-                            &call_id,
-                            Some(&tracker),
-                        );
-                        let content = emitter.finish(event_ctx, out, delta.as_ref()).await?;
-                        Ok(boxed_tool_output(FunctionToolOutput::from_text(
-                            content,
-                            Some(true),
-                        )))
-                    }
-                }
-            }
-            codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(err) => {
-                Err(FunctionCallError::RespondToModel(format!(
-                    "text_editor patch verification failed: {err}"
-                )))
-            }
-            codex_apply_patch::MaybeApplyPatchVerified::ShellParseError(err) => {
-                tracing::trace!("text_editor: failed to parse generated patch: {err:?}");
-                Err(FunctionCallError::RespondToModel(
-                    "text_editor: internal error generating patch".to_string(),
-                ))
+                    .map(|result| result.output);
+                let (out, delta) = match out {
+                    Ok(output) => (Ok(output.exec_output), Some(output.delta)),
+                    Err(error) => (Err(error), Some(runtime.committed_delta().clone())),
+                };
+                let event_ctx =
+                    ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, Some(&tracker));
+                emitter.finish(event_ctx, out, delta.as_ref()).await?
             }
-            codex_apply_patch::MaybeApplyPatchVerified::NotApplyPatch => {
-                Err(FunctionCallError::RespondToModel(
-                    "text_editor: internal error – generated patch not recognized".to_string(),
-                ))
+        };
+
+        let content = if is_undo {
+            let restored = history.pop(&file_path).flatten();
+            let summary = undo_summary(&args.path, restored.as_deref());
+            format!("{content}\n{summary}")
+        } else {
+            if let Ok(previous) = current_content {
+                history.record(file_path, previous);
             }
+            content
+        };
+        Ok(boxed_tool_output(FunctionToolOutput::from_text(
+            content,
+            Some(true),
+        )))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Patch verification
+// ---------------------------------------------------------------------------
+
+/// Parse and verify a generated patch against the files on disk, mapping any
+/// failure to an error that names the command, the file and the failing hunk.
+async fn verify_generated_patch(
+    command: &str,
+    path: &str,
+    patch: String,
+    cwd: &PathUri,
+    fs: &dyn ExecutorFileSystem,
+    sandbox: Option<&FileSystemSandboxContext>,
+) -> Result<ApplyPatchAction, FunctionCallError> {
+    let argv = vec!["apply_patch".to_string(), patch];
+    match codex_apply_patch::maybe_parse_apply_patch_verified(&argv, cwd, fs, sandbox).await {
+        MaybeApplyPatchVerified::Body(action) => Ok(action),
+        MaybeApplyPatchVerified::CorrectnessError(err) => Err(FunctionCallError::RespondToModel(
+            describe_patch_error(command, path, &argv[1], &err),
+        )),
+        MaybeApplyPatchVerified::ShellParseError(err) => {
+            tracing::trace!("text_editor: failed to parse generated patch: {err:?}");
+            Err(FunctionCallError::RespondToModel(format!(
+                "text_editor {command} failed for {path}: internal error generating patch"
+            )))
+        }
+        MaybeApplyPatchVerified::NotApplyPatch => Err(FunctionCallError::RespondToModel(
+            "text_editor: internal error – generated patch not recognized".to_string(),
+        )),
+    }
+}
+
+fn describe_patch_error(command: &str, path: &str, patch: &str, err: &ApplyPatchError) -> String {
+    match err {
+        ApplyPatchError::ComputeReplacements(detail) => format!(
+            "text_editor {command} failed for {path}: the edit does not apply to the current file contents. \
+             {detail}\nRe-read the file with the shell tool (e.g. `cat`) and retry with text that matches it exactly."
+        ),
+        ApplyPatchError::ParseError(ParseError::InvalidHunkError {
+            message,
+            line_number,
+        }) => {
+            let line = patch
+                .lines()
+                .nth(line_number.saturating_sub(1))
+                .unwrap_or_default();
+            format!(
+                "text_editor {command} failed for {path}: generated patch is invalid at line {line_number} ({line:?}): {message}"
+            )
         }
+        other => format!("text_editor {command} failed for {path}: {other}"),
     }
 }
 
@@ -337,16 +609,102 @@ fn generate_create_patch(path: &str, file_text: &str) -> String {
     patch
 }
 
+/// Builds the patch for `create`, refusing to replace `existing` content
+/// unless `overwrite` is set.
+fn generate_create_or_overwrite_patch(
+    path: &str,
+    file_text: &str,
+    existing: Option<&str>,
+    overwrite: bool,
+) -> Result<String, FunctionCallError> {
+    match existing {
+        None => Ok(generate_create_patch(path, file_text)),
+        Some(existing) if overwrite => Ok(generate_rewrite_patch(path, existing, file_text)),
+        Some(_) => Err(FunctionCallError::RespondToModel(format!(
+            "file already exists at {path}; use str_replace to edit it, or pass overwrite: true to replace it"
+        ))),
+    }
+}
+
+/// Builds an update patch that replaces all of `current` with `new`.
+fn generate_rewrite_patch(path: &str, current: &str, new: &str) -> String {
+    let mut patch = String::from("*** Begin Patch\n");
+    patch.push_str(&format!("*** Update File: {path}\n"));
+    patch.push_str("@@\n");
+    for line in current.lines() {
+        patch.push('-');
+        patch.push_str(line);
+        patch.push('\n');
+    }
+    for line in new.lines() {
+        patch.push('+');
+        patch.push_str(line);
+        patch.push('\n');
+    }
+    patch.push_str("*** End Patch\n");
+    patch
+}
+
 fn generate_delete_patch(path: &str) -> String {
     format!("*** Begin Patch\n*** Delete File: {path}\n*** End Patch\n")
 }
 
+/// Builds a patch that brings `path` from `current` back to `previous`, where
+/// `None` means the file does not exist.
+fn generate_undo_patch(
+    path: &str,
+    previous: Option<&str>,
+    current: Option<&str>,
+) -> Result<String, FunctionCallError> {
+    match (previous, current) {
+        (None, Some(_)) => Ok(generate_delete_patch(path)),
+        (Some(previous), None) => Ok(generate_create_patch(path, previous)),
+        (Some(previous), Some(current)) => Ok(generate_rewrite_patch(path, current, previous)),
+        (None, None) => Err(FunctionCallError::RespondToModel(format!(
+            "nothing to undo: {path} was created and has since been removed"
+        ))),
+    }
+}
+
+/// Describes a completed undo, echoing the restored content up to
+/// [`MAX_UNDO_OUTPUT_LINES`] lines and [`MAX_UNDO_OUTPUT_BYTES`] bytes.
+fn undo_summary(path: &str, restored: Option<&str>) -> String {
+    let Some(restored) = restored else {
+        return format!("Undo removed {path}, which did not exist before the edit.");
+    };
+    let total_lines = restored.lines().count();
+    let mut shown = String::new();
+    let mut shown_lines = 0;
+    for line in restored.split_inclusive('\n') {
+        if shown_lines == MAX_UNDO_OUTPUT_LINES
+            || shown.len() + line.len() > MAX_UNDO_OUTPUT_BYTES
+        {
+            break;
+        }
+        shown.push_str(line);
+        shown_lines += 1;
+    }
+    let mut summary = format!(
+        "Restored {path} ({total_lines} lines) to its content before the last text_editor edit:\n{shown}"
+    );
+    if shown_lines < total_lines {
+        if !summary.ends_with('\n') {
+            summary.push('\n');
+        }
+        let hidden = total_lines - shown_lines;
+        summary = format!("{summary}[... {hidden} more lines not shown]");
+    }
+    summary
+}
+
 fn generate_str_replace_patch(
     path: &str,
     old_str: &str,
     new_str: &str,
     file_content: &str,
 ) -> Result<String, FunctionCallError> {
+    reject_noop_replacement(old_str, new_str)?;
+
     // Find all occurrences of old_str.
     let matches: Vec<usize> = file_content
         .match_indices(old_str)
@@ -431,13 +789,390 @@ fn generate_str_replace_patch(
     Ok(patch)
 }
 
+/// An edit whose replacement equals the original would produce a patch that
+/// changes nothing, so it is rejected before reaching the apply pipeline.
+fn reject_noop_replacement(old_str: &str, new_str: &str) -> Result<(), FunctionCallError> {
+    if old_str == new_str {
+        return Err(FunctionCallError::RespondToModel(
+            "old_str and new_str are identical; no change needed".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Like [`generate_str_replace_patch`] but replaces every non-overlapping
+/// occurrence of `old_str`, scanning left to right like [`str::replace`].
+///
+/// Matches whose changed lines are close enough that their context would
+/// overlap are emitted as a single hunk, with the unchanged lines between them
+/// kept as context.
+fn generate_str_replace_all_patch(
+    path: &str,
+    old_str: &str,
+    new_str: &str,
+    file_content: &str,
+) -> Result<String, FunctionCallError> {
+    if old_str.is_empty() {
+        return Err(FunctionCallError::RespondToModel(
+            "old_str must not be empty for str_replace_all.".to_string(),
+        ));
+    }
+    reject_noop_replacement(old_str, new_str)?;
+
+    let lines: Vec<&str> = file_content.lines().collect();
+    let mut line_starts = Vec::with_capacity(lines.len());
+    let mut byte_offset = 0;
+    for line in &lines {
+        line_starts.push(byte_offset);
+        byte_offset += line.len() + 1; // +1 for \n
+    }
+    let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset) - 1;
+
+    // Line ranges touched by each match, merged when they share a line.
+    let mut regions: Vec<(usize, usize)> = Vec::new();
+    for (match_start, matched) in file_content.match_indices(old_str) {
+        let start_line = line_of(match_start);
+        let end_line = line_of(match_start + matched.len() - 1);
+        match regions.last_mut() {
+            Some((_, last_end)) if start_line <= *last_end => {
+                *last_end = (*last_end).max(end_line);
+            }
+            _ => regions.push((start_line, end_line)),
+        }
+    }
+
+    if regions.is_empty() {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "old_str not found in {path}. Make sure the string matches exactly."
+        )));
+    }
+
+    let mut patch = String::from("*** Begin Patch\n");
+    patch.push_str(&format!("*** Update File: {path}\n"));
+
+    let push_lines = |patch: &mut String, prefix: char, lines: &[&str]| {
+        for line in lines {
+            patch.push(prefix);
+            patch.push_str(line);
+            patch.push('\n');
+        }
+    };
+
+    let mut i = 0;
+    while i < regions.len() {
+        // Extend the hunk while the gap to the next region fits in the
+        // combined post- and pre-context.
+        let mut j = i;
+        while j + 1 < regions.len() && regions[j + 1].0 - regions[j].1 - 1 <= 2 * CONTEXT_LINES {
+            j += 1;
+        }
+
+        let ctx_start = regions[i].0.saturating_sub(CONTEXT_LINES);
+        let ctx_end = (regions[j].1 + CONTEXT_LINES + 1).min(lines.len());
+
+        patch.push_str("@@\n");
+        push_lines(&mut patch, ' ', &lines[ctx_start..regions[i].0]);
+        let group = &regions[i..=j];
+        for (k, &(start_line, end_line)) in group.iter().enumerate() {
+            // Slice the original text (including the trailing newline) so
+            // matches that end on a line break are still found.
+            let region_end = line_starts
+                .get(end_line + 1)
+                .copied()
+                .unwrap_or(file_content.len());
+            let new_region =
+                file_content[line_starts[start_line]..region_end].replace(old_str, new_str);
+            push_lines(&mut patch, '-', &lines[start_line..=end_line]);
+            push_lines(&mut patch, '+', &new_region.lines().collect::<Vec<_>>());
+            let gap_end = group
+                .get(k + 1)
+                .map_or(ctx_end, |(next_start, _)| *next_start);
+            push_lines(&mut patch, ' ', &lines[(end_line + 1)..gap_end]);
+        }
+
+        i = j + 1;
+    }
+
+    patch.push_str("*** End Patch\n");
+    Ok(patch)
+}
+
+/// Converts a unified diff for `path` into an update patch against
+/// `file_content`. File headers (`diff`, `index`, `---`, `+++`) must resolve
+/// to `path` relative to `cwd`, and each hunk is checked against the line counts in its
+/// `@@ -a,b +c,d @@` header so a truncated or miscounted diff is rejected
+/// before it reaches the file. The header's start line decides which
+/// occurrence a repeated block refers to.
+fn generate_apply_diff_patch(
+    path: &str,
+    diff: &str,
+    file_content: &str,
+    cwd: &PathUri,
+) -> Result<String, FunctionCallError> {
+    let hunks = parse_unified_diff(path, diff, cwd)?;
+    let lines: Vec<&str> = file_content.lines().collect();
+
+    let mut patch = String::from("*** Begin Patch\n");
+    patch.push_str(&format!("*** Update File: {path}\n"));
+    // Line from which the apply pipeline searches for the next hunk.
+    let mut search_from = 0;
+    for hunk in hunks {
+        let (body, old_start) = anchor_pure_insertion(hunk, &lines);
+        let old_block: Vec<&str> = body
+            .iter()
+            .filter(|(prefix, _)| *prefix != '+')
+            .map(|(_, line)| line.as_str())
+            .collect();
+
+        // Without an exact match near the stated line, fall back to the
+        // pipeline's own search so it reports the lines it could not find.
+        let located = (!old_block.is_empty())
+            .then(|| nearest_match(&lines, &old_block, search_from, old_start))
+            .flatten();
+        patch.push_str("@@\n");
+        if let Some(start) = located {
+            // Add leading context until the hunk's first match from
+            // `search_from` is the intended one.
+            let mut context_start = start;
+            while context_start > search_from
+                && first_match(
+                    &lines,
+                    &lines[context_start..start + old_block.len()],
+                    search_from,
+                ) != Some(context_start)
+            {
+                context_start -= 1;
+            }
+            for line in &lines[context_start..start] {
+                patch.push(' ');
+                patch.push_str(line);
+                patch.push('\n');
+            }
+            search_from = start + old_block.len();
+        }
+        for (prefix, line) in &body {
+            patch.push(*prefix);
+            patch.push_str(line);
+            patch.push('\n');
+        }
+    }
+    patch.push_str("*** End Patch\n");
+    Ok(patch)
+}
+
+/// One hunk of a unified diff.
+struct DiffHunk {
+    /// 0-based index of the first old line; for a pure insertion, the index
+    /// the new lines are inserted at.
+    old_start: usize,
+    lines: Vec<(char, String)>,
+}
+
+/// Parses and validates a single-file unified diff for `path`.
+fn parse_unified_diff(
+    path: &str,
+    diff: &str,
+    cwd: &PathUri,
+) -> Result<Vec<DiffHunk>, FunctionCallError> {
+    let invalid = |message: String| {
+        FunctionCallError::RespondToModel(format!("invalid diff for {path}: {message}"))
+    };
+
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+    let mut saw_file_header = false;
+    // Old and new lines still owed by the current hunk.
+    let mut remaining = (0, 0);
+    for (index, line) in diff.lines().enumerate() {
+        let line_number = index + 1;
+        if line.starts_with('\\') {
+            // "\ No newline at end of file"
+            continue;
+        }
+        if remaining == (0, 0) {
+            if let Some(header) = line.strip_prefix("@@") {
+                let header = parse_hunk_header(header).ok_or_else(|| {
+                    invalid(format!(
+                        "malformed hunk header at line {line_number}: {line:?}"
+                    ))
+                })?;
+                remaining = (header.old_count, header.new_count);
+                let old_start = if header.old_count == 0 {
+                    header.old_start
+                } else {
+                    header.old_start.saturating_sub(1)
+                };
+                hunks.push(DiffHunk {
+                    old_start,
+                    lines: Vec::new(),
+                });
+            } else if let Some(header_path) = line
+                .strip_prefix("--- ")
+                .or_else(|| line.strip_prefix("+++ "))
+            {
+                if saw_file_header {
+                    return Err(invalid(
+                        "apply_diff edits a single file; send one call per file".to_string(),
+                    ));
+                }
+                if !diff_header_names_path(header_path, path, cwd) {
+                    return Err(invalid(format!(
+                        "line {line_number} names a different file: {line:?}"
+                    )));
+                }
+                saw_file_header |= line.starts_with("+++ ");
+            } else if !(line.trim().is_empty()
+                || line.starts_with("diff ")
+                || line.starts_with("index "))
+            {
+                return Err(invalid(format!(
+                    "unexpected line {line_number} outside a hunk: {line:?}"
+                )));
+            }
+            continue;
+        }
+
+        let hunk_number = hunks.len();
+        // Some tools strip the leading space from blank context lines.
+        let (prefix, content) = match line.chars().next() {
+            None => (' ', ""),
+            Some(prefix @ (' ' | '-' | '+')) => (prefix, &line[1..]),
+            Some(_) => {
+                return Err(invalid(format!(
+                    "line {line_number} in hunk {hunk_number} must start with ' ', '-' or '+': {line:?}"
+                )));
+            }
+        };
+        let (old, new) = &mut remaining;
+        let fits = match prefix {
+            ' ' => *old > 0 && *new > 0,
+            '-' => *old > 0,
+            _ => *new > 0,
+        };
+        if !fits {
+            return Err(invalid(format!(
+                "hunk {hunk_number} has more lines than its header declares at line {line_number}"
+            )));
+        }
+        if prefix != '+' {
+            *old -= 1;
+        }
+        if prefix != '-' {
+            *new -= 1;
+        }
+        if let Some(hunk) = hunks.last_mut() {
+            hunk.lines.push((prefix, content.to_string()));
+        }
+    }
+
+    if hunks.is_empty() {
+        return Err(invalid(
+            "no hunks found; expected a unified diff with @@ headers".to_string(),
+        ));
+    }
+    let (old, new) = remaining;
+    if (old, new) != (0, 0) {
+        let hunk_number = hunks.len();
+        return Err(invalid(format!(
+            "hunk {hunk_number} is truncated: expected {old} more old and {new} more new lines"
+        )));
+    }
+    Ok(hunks)
+}
+
+/// Whether a `---`/`+++` header path resolves to the same file as `path`,
+/// both taken relative to `cwd`. `/dev/null`, trailing timestamps and one
+/// leading git `a/`/`b/` prefix are accepted.
+fn diff_header_names_path(header_path: &str, path: &str, cwd: &PathUri) -> bool {
+    let header_path = header_path
+        .split('\t')
+        .next()
+        .unwrap_or_default()
+        .trim_end();
+    if header_path == "/dev/null" {
+        return true;
+    }
+    let Ok(target) = cwd.join(path) else {
+        return false;
+    };
+    // `git diff --no-prefix` omits the prefix, so the header is also tried as is.
+    let unprefixed = header_path
+        .strip_prefix("a/")
+        .or_else(|| header_path.strip_prefix("b/"));
+    [Some(header_path), unprefixed]
+        .into_iter()
+        .flatten()
+        .any(|candidate| cwd.join(candidate).is_ok_and(|resolved| resolved == target))
+}
+
+/// A pure insertion has no old lines for the apply pipeline to locate, so the
+/// file line after (or, at the end, before) the insertion point is added as
+/// context. Returns the hunk body and the index of its first old line.
+fn anchor_pure_insertion(hunk: DiffHunk, lines: &[&str]) -> (Vec<(char, String)>, usize) {
+    let DiffHunk {
+        old_start,
+        lines: mut body,
+    } = hunk;
+    if body.iter().any(|(prefix, _)| *prefix != '+') {
+        return (body, old_start);
+    }
+    let at = old_start.min(lines.len());
+    if let Some(next) = lines.get(at) {
+        body.push((' ', (*next).to_string()));
+        (body, at)
+    } else if let Some(previous) = at.checked_sub(1).and_then(|index| lines.get(index)) {
+        body.insert(0, (' ', (*previous).to_string()));
+        (body, at - 1)
+    } else {
+        (body, at)
+    }
+}
+
+/// The start of the exact match of `block` in `lines` at or after `from` that
+/// is closest to `preferred`.
+fn nearest_match(lines: &[&str], block: &[&str], from: usize, preferred: usize) -> Option<usize> {
+    (from..=lines.len().checked_sub(block.len())?)
+        .filter(|&start| lines[start..start + block.len()] == *block)
+        .min_by_key(|&start| start.abs_diff(preferred))
+}
+
+/// The start of the first exact match of `block` in `lines` at or after `from`.
+fn first_match(lines: &[&str], block: &[&str], from: usize) -> Option<usize> {
+    (from..=lines.len().checked_sub(block.len())?)
+        .find(|&start| lines[start..start + block.len()] == *block)
+}
+
+/// `@@ -a,b +c,d @@` ranges. A range without a count (`-3`) covers one line.
+struct HunkHeader {
+    old_start: usize,
+    old_count: usize,
+    new_count: usize,
+}
+
+/// Parses the text after `@@` in a hunk header.
+fn parse_hunk_header(header: &str) -> Option<HunkHeader> {
+    let (ranges, _) = header.split_once("@@")?;
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let mut parts = ranges.split_whitespace();
+    let (old_start, old_count) = range(parts.next()?.strip_prefix('-')?)?;
+    let (_, new_count) = range(parts.next()?.strip_prefix('+')?)?;
+    Some(HunkHeader {
+        old_start,
+        old_count,
+        new_count,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
     use super::*;
     use codex_apply_patch::ApplyPatchFileChange;
-    use codex_apply_patch::MaybeApplyPatchVerified;
     use codex_exec_server::LOCAL_FS;
     use codex_utils_path_uri::PathUri;
     use tempfile::TempDir;
@@ -457,6 +1192,82 @@ mod tests {
             ))
     }
 
+    fn diff_cwd() -> PathUri {
+        PathUri::parse("file:///repo").expect("cwd should parse")
+    }
+
+    fn text_editor_parameters(strict: bool) -> (bool, JsonSchema) {
+        match create_text_editor_tool(strict) {
+            ToolSpec::Function(tool) => (tool.strict, tool.parameters),
+            other => panic!("expected function tool, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn text_editor_tool_is_lenient_by_default() {
+        let (strict, parameters) = text_editor_parameters(/*strict*/ false);
+        assert!(!strict);
+        assert_eq!(
+            parameters.required,
+            Some(vec!["command".to_string(), "path".to_string()])
+        );
+        let properties = parameters.properties.expect("properties");
+        assert_eq!(
+            properties["old_str"].schema_type,
+            Some(JsonSchemaType::Single(JsonSchemaPrimitiveType::String))
+        );
+    }
+
+    #[test]
+    fn text_editor_tool_strict_variant_requires_all_properties() {
+        let provider: codex_model_provider_info::ModelProviderInfo = toml::from_str(
+            r#"
+name = "Strict"
+base_url = "https://example.com/v1"
+wire_api = "chat"
+
+[compat]
+strict_tool_schemas = true
+"#,
+        )
+        .expect("provider toml");
+        let (strict, parameters) = text_editor_parameters(provider.prefers_strict_tool_schemas());
+
+        assert!(strict);
+        assert_eq!(parameters.additional_properties, Some(false.into()));
+        assert_eq!(
+            parameters.required,
+            Some(vec![
+                "command".to_string(),
+                "diff".to_string(),
+                "file_text".to_string(),
+                "new_str".to_string(),
+                "old_str".to_string(),
+                "overwrite".to_string(),
+                "path".to_string(),
+            ])
+        );
+        let properties = parameters.properties.expect("properties");
+        let nullable_string = Some(JsonSchemaType::Multiple(vec![
+            JsonSchemaPrimitiveType::String,
+            JsonSchemaPrimitiveType::Null,
+        ]));
+        assert_eq!(properties["file_text"].schema_type, nullable_string);
+        assert_eq!(properties["old_str"].schema_type, nullable_string);
+        assert_eq!(properties["new_str"].schema_type, nullable_string);
+        assert_eq!(
+            properties["overwrite"].schema_type,
+            Some(JsonSchemaType::Multiple(vec![
+                JsonSchemaPrimitiveType::Boolean,
+                JsonSchemaPrimitiveType::Null,
+            ]))
+        );
+        assert_eq!(
+            properties["command"].schema_type,
+            Some(JsonSchemaType::Single(JsonSchemaPrimitiveType::String))
+        );
+    }
+
     #[test]
     fn create_patch_round_trips() {
         let tmp = TempDir::new().unwrap();
@@ -478,6 +1289,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn create_refuses_to_overwrite_existing_file() {
+        assert_eq!(
+            generate_create_or_overwrite_patch(
+                "f.txt",
+                "new\n",
+                Some("old\n"),
+                /*overwrite*/ false
+            ),
+            Err(FunctionCallError::RespondToModel(
+                "file already exists at f.txt; use str_replace to edit it, or pass overwrite: true to replace it"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn create_with_overwrite_replaces_existing_file() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("f.txt"), "old\ncontent\n").unwrap();
+        let patch = generate_create_or_overwrite_patch(
+            "f.txt",
+            "new\n",
+            Some("old\ncontent\n"),
+            /*overwrite*/ true,
+        )
+        .unwrap();
+        match parse_patch(&patch, tmp.path()) {
+            MaybeApplyPatchVerified::Body(action) => {
+                let changes = action.changes();
+                assert_eq!(changes.len(), 1);
+                let (_, change) = changes.iter().next().unwrap();
+                match change {
+                    ApplyPatchFileChange::Update { new_content, .. } => {
+                        assert_eq!(new_content, "new\n");
+                    }
+                    other => panic!("expected Update, got {other:?}"),
+                }
+            }
+            other => panic!("expected Body, got {other:?}"),
+        }
+    }
+
     #[test]
     fn delete_patch_round_trips() {
         let tmp = TempDir::new().unwrap();
@@ -560,6 +1414,21 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn str_replace_rejects_identical_old_and_new_str() {
+        let expected = Err(FunctionCallError::RespondToModel(
+            "old_str and new_str are identical; no change needed".to_string(),
+        ));
+        assert_eq!(
+            generate_str_replace_patch("f.txt", "hello", "hello", "hello\nworld\n"),
+            expected
+        );
+        assert_eq!(
+            generate_str_replace_all_patch("f.txt", "hello", "hello", "hello\nworld\n"),
+            expected
+        );
+    }
+
     #[test]
     fn str_replace_near_file_start() {
         let file_content = "first\nsecond\nthird\nfourth\n";
@@ -578,4 +1447,375 @@ mod tests {
         assert!(patch.contains("+LAST\n"));
         assert!(patch.contains(" ccc\n"));
     }
+
+    fn replace_all_new_content(file_content: &str, old_str: &str, new_str: &str) -> String {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("f.txt"), file_content).unwrap();
+        let patch =
+            generate_str_replace_all_patch("f.txt", old_str, new_str, file_content).unwrap();
+        match parse_patch(&patch, tmp.path()) {
+            MaybeApplyPatchVerified::Body(action) => {
+                let changes = action.changes();
+                assert_eq!(changes.len(), 1);
+                match changes.values().next().unwrap() {
+                    ApplyPatchFileChange::Update { new_content, .. } => new_content.clone(),
+                    other => panic!("expected Update, got {other:?}"),
+                }
+            }
+            other => panic!("expected Body, got {other:?}\npatch:\n{patch}"),
+        }
+    }
+
+    #[test]
+    fn str_replace_all_replaces_three_occurrences() {
+        let file_content = "let foo = 1;\n\
+            a\nb\nc\nd\ne\nf\ng\nh\n\
+            println!(\"{foo}\");\n\
+            i\nj\nk\nl\nm\nn\no\np\n\
+            foo += 1;\n";
+        assert_eq!(
+            replace_all_new_content(file_content, "foo", "bar"),
+            file_content.replace("foo", "bar")
+        );
+    }
+
+    #[test]
+    fn str_replace_all_preserves_unrelated_lines() {
+        let file_content = "keep one\nold\nkeep two\nold old\nkeep three\n";
+        assert_eq!(
+            replace_all_new_content(file_content, "old", "new"),
+            "keep one\nnew\nkeep two\nnew new\nkeep three\n"
+        );
+    }
+
+    #[test]
+    fn str_replace_all_handles_adjacent_and_multiline_matches() {
+        let file_content = "aaaa\nx\naa\naa\ny\n";
+        assert_eq!(
+            replace_all_new_content(file_content, "aa", "b"),
+            "bb\nx\nb\nb\ny\n"
+        );
+        assert_eq!(
+            replace_all_new_content(file_content, "aa\naa\n", "z\n"),
+            "aaaa\nx\nz\ny\n"
+        );
+    }
+
+    #[test]
+    fn str_replace_all_not_found_errors() {
+        let result = generate_str_replace_all_patch("f.txt", "MISSING", "x", "hello\nworld\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn undo_patch_restores_content_after_str_replace() {
+        let tmp = TempDir::new().unwrap();
+        let original = "line one\nline two\nline three\n";
+        let edited = "line one\nline TWO\nline three\n";
+        std::fs::write(tmp.path().join("f.txt"), edited).unwrap();
+
+        let history = StructuredEditHistory::default();
+        let path = PathUri::from_host_native_path(&tmp.path().join("f.txt")).unwrap();
+        history.record(path.clone(), Some(original.to_string()));
+
+        let previous = history.previous_content(&path, "f.txt").unwrap();
+        let patch = generate_undo_patch("f.txt", previous.as_deref(), Some(edited)).unwrap();
+        match parse_patch(&patch, tmp.path()) {
+            MaybeApplyPatchVerified::Body(action) => {
+                match action.changes().values().next().unwrap() {
+                    ApplyPatchFileChange::Update { new_content, .. } => {
+                        assert_eq!(new_content, original);
+                    }
+                    other => panic!("expected Update, got {other:?}"),
+                }
+            }
+            other => panic!("expected Body, got {other:?}"),
+        }
+
+        let restored = history.pop(&path).flatten();
+        assert_eq!(restored.as_deref(), Some(original));
+        assert!(history.previous_content(&path, "f.txt").is_err());
+        assert_eq!(
+            undo_summary("f.txt", restored.as_deref()),
+            "Restored f.txt (3 lines) to its content before the last text_editor edit:\n\
+             line one\nline two\nline three\n"
+        );
+    }
+
+    #[test]
+    fn undo_summary_truncates_long_files() {
+        let restored: String = (1..=MAX_UNDO_OUTPUT_LINES + 5)
+            .map(|line| format!("line {line}\n"))
+            .collect();
+        let summary = undo_summary("f.txt", Some(&restored));
+
+        let last_shown = format!("line {MAX_UNDO_OUTPUT_LINES}\n");
+        let first_hidden = format!("line {}\n", MAX_UNDO_OUTPUT_LINES + 1);
+        assert!(summary.contains(&last_shown));
+        assert!(!summary.contains(&first_hidden));
+        assert!(summary.ends_with("[... 5 more lines not shown]"));
+        assert_eq!(
+            undo_summary("new.txt", None),
+            "Undo removed new.txt, which did not exist before the edit."
+        );
+    }
+
+    #[test]
+    fn undo_history_keeps_latest_entries_per_file() {
+        let tmp = TempDir::new().unwrap();
+        let history = StructuredEditHistory::default();
+        let path = PathUri::from_host_native_path(&tmp.path().join("f.txt")).unwrap();
+        for version in 0..=MAX_UNDO_ENTRIES_PER_FILE {
+            history.record(path.clone(), Some(version.to_string()));
+        }
+
+        for version in (1..=MAX_UNDO_ENTRIES_PER_FILE).rev() {
+            assert_eq!(history.pop(&path), Some(Some(version.to_string())));
+        }
+        assert_eq!(history.pop(&path), None);
+    }
+
+    #[test]
+    fn undo_history_evicts_oldest_entries_over_byte_limit() {
+        let tmp = TempDir::new().unwrap();
+        let history = StructuredEditHistory::default();
+        let first = PathUri::from_host_native_path(&tmp.path().join("a.txt")).unwrap();
+        let second = PathUri::from_host_native_path(&tmp.path().join("b.txt")).unwrap();
+        let half = "x".repeat(MAX_UNDO_HISTORY_BYTES / 2 + 1);
+        history.record(first.clone(), Some(half.clone()));
+        history.record(second.clone(), Some(half.clone()));
+
+        assert!(history.previous_content(&first, "a.txt").is_err());
+        assert_eq!(history.pop(&second), Some(Some(half)));
+    }
+
+    #[test]
+    fn undo_patch_deletes_file_created_in_session() {
+        let patch = generate_undo_patch("new.txt", None, Some("hello\n")).unwrap();
+        assert_eq!(patch, generate_delete_patch("new.txt"));
+    }
+
+    #[test]
+    fn undo_without_prior_edit_errors() {
+        let tmp = TempDir::new().unwrap();
+        let history = StructuredEditHistory::default();
+        let path = PathUri::from_host_native_path(&tmp.path().join("f.txt")).unwrap();
+        assert_eq!(
+            history.previous_content(&path, "f.txt"),
+            Err(FunctionCallError::RespondToModel(
+                "no text_editor edit to undo for f.txt in this session".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn stale_str_replace_patch_reports_failing_file() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("f.txt"), "alpha\nbeta\n").unwrap();
+        // Generated against contents that no longer match the file on disk.
+        let patch =
+            generate_str_replace_patch("f.txt", "gamma", "delta", "alpha\ngamma\n").unwrap();
+        let cwd = PathUri::from_host_native_path(tmp.path()).unwrap();
+        let err = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build runtime")
+            .block_on(verify_generated_patch(
+                "str_replace",
+                "f.txt",
+                patch,
+                &cwd,
+                LOCAL_FS.as_ref(),
+                None,
+            ))
+            .expect_err("stale patch should not verify");
+        let FunctionCallError::RespondToModel(message) = err else {
+            panic!("expected RespondToModel, got {err:?}");
+        };
+        assert!(
+            message.starts_with(
+                "text_editor str_replace failed for f.txt: the edit does not apply to the current file contents."
+            ),
+            "{message}"
+        );
+        assert!(
+            message.contains("Failed to find expected lines"),
+            "{message}"
+        );
+        assert!(
+            message.contains("Re-read the file with the shell tool"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn apply_diff_patch_applies_unified_diff() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(
+            tmp.path().join("f.txt"),
+            "one\ntwo\nthree\nfour\nfive\nsix\nseven\n",
+        )
+        .unwrap();
+        let diff = "\
+--- a/f.txt
++++ b/f.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++TWO
+ three
+@@ -5,3 +5,4 @@ four
+ five
+ six
++six and a half
+ seven
+";
+
+        let patch = generate_apply_diff_patch(
+            "f.txt",
+            diff,
+            "one\ntwo\nthree\nfour\nfive\nsix\nseven\n",
+            &diff_cwd(),
+        )
+        .unwrap();
+        match parse_patch(&patch, tmp.path()) {
+            MaybeApplyPatchVerified::Body(action) => {
+                let changes = action.changes();
+                assert_eq!(changes.len(), 1);
+                let (_, change) = changes.iter().next().unwrap();
+                match change {
+                    ApplyPatchFileChange::Update { new_content, .. } => assert_eq!(
+                        new_content,
+                        "one\nTWO\nthree\nfour\nfive\nsix\nsix and a half\nseven\n"
+                    ),
+                    other => panic!("expected Update, got {other:?}"),
+                }
+            }
+            other => panic!("expected Body, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn apply_diff_with_mismatched_context_reports_failing_lines() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("f.txt"), "alpha\nbeta\ngamma\n").unwrap();
+        let diff = "@@ -1,3 +1,3 @@\n alpha\n-BETA\n+delta\n gamma\n";
+
+        let patch =
+            generate_apply_diff_patch("f.txt", diff, "alpha\nbeta\ngamma\n", &diff_cwd()).unwrap();
+        let cwd = PathUri::from_host_native_path(tmp.path()).unwrap();
+        let err = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build runtime")
+            .block_on(verify_generated_patch(
+                "apply_diff",
+                "f.txt",
+                patch,
+                &cwd,
+                LOCAL_FS.as_ref(),
+                None,
+            ))
+            .expect_err("mismatched diff should not verify");
+        let FunctionCallError::RespondToModel(message) = err else {
+            panic!("expected RespondToModel, got {err:?}");
+        };
+        assert!(
+            message.starts_with(
+                "text_editor apply_diff failed for f.txt: the edit does not apply to the current file contents."
+            ),
+            "{message}"
+        );
+        assert!(message.contains("BETA"), "{message}");
+    }
+
+    #[test]
+    fn apply_diff_rejects_hunk_that_disagrees_with_header() {
+        let diff = "@@ -1,2 +1,2 @@\n alpha\n-beta\n+delta\n gamma\n";
+        assert_eq!(
+            generate_apply_diff_patch("f.txt", diff, "alpha\nbeta\ngamma\n", &diff_cwd()),
+            Err(FunctionCallError::RespondToModel(
+                "invalid diff for f.txt: hunk 1 has more lines than its header declares at line 5"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn apply_diff_rejects_multi_file_diff() {
+        let diff = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-c
++d
+";
+        assert_eq!(
+            generate_apply_diff_patch("a.txt", diff, "a\n", &diff_cwd()),
+            Err(FunctionCallError::RespondToModel(
+                "invalid diff for a.txt: apply_diff edits a single file; send one call per file"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn apply_diff_uses_header_line_for_repeated_blocks() {
+        let tmp = TempDir::new().unwrap();
+        let original = "start\nitem\nvalue\nend\nitem\nvalue\nend\n";
+        std::fs::write(tmp.path().join("f.txt"), original).unwrap();
+        let diff = "@@ -5,2 +5,2 @@\n item\n-value\n+VALUE\n";
+
+        let patch = generate_apply_diff_patch("f.txt", diff, original, &diff_cwd()).unwrap();
+        match parse_patch(&patch, tmp.path()) {
+            MaybeApplyPatchVerified::Body(action) => {
+                match action.changes().values().next().unwrap() {
+                    ApplyPatchFileChange::Update { new_content, .. } => {
+                        assert_eq!(new_content, "start\nitem\nvalue\nend\nitem\nVALUE\nend\n")
+                    }
+                    other => panic!("expected Update, got {other:?}"),
+                }
+            }
+            other => panic!("expected Body, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn apply_diff_rejects_headers_for_another_file() {
+        let diff = "\
+--- a/other.txt
++++ b/other.txt
+@@ -1 +1 @@
+-a
++b
+";
+        assert_eq!(
+            generate_apply_diff_patch("f.txt", diff, "a\n", &diff_cwd()),
+            Err(FunctionCallError::RespondToModel(
+                "invalid diff for f.txt: line 1 names a different file: \"--- a/other.txt\""
+                    .to_string()
+            ))
+        );
+
+        let same_basename = "--- a/lib/f.txt\n+++ b/lib/f.txt\n@@ -1 +1 @@\n-a\n+b\n";
+        assert_eq!(
+            generate_apply_diff_patch("src/f.txt", same_basename, "a\n", &diff_cwd()),
+            Err(FunctionCallError::RespondToModel(
+                "invalid diff for src/f.txt: line 1 names a different file: \"--- a/lib/f.txt\""
+                    .to_string()
+            ))
+        );
+        assert!(
+            generate_apply_diff_patch("f.txt", same_basename, "a\n", &diff_cwd()).is_err(),
+            "a header in a subdirectory should not match a file at the root"
+        );
+
+        let equivalent = "--- ./src/f.txt\n+++ /repo/src/f.txt\n@@ -1 +1 @@\n-a\n+b\n";
+        assert!(generate_apply_diff_patch("src/f.txt", equivalent, "a\n", &diff_cwd()).is_ok());
+    }
 }
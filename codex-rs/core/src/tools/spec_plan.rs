@@ -769,7 +769,9 @@ fn add_core_utility_tools(context: &CoreToolPlanContext<'_>, planned_tools: &mut
             Some(codex_protocol::openai_models::ApplyPatchToolType::Structured)
         )
     {
-        planned_tools.add(StructuredEditHandler);
+        planned_tools.add(StructuredEditHandler::new(
+            turn_context.provider.info().prefers_strict_tool_schemas(),
+        ));
     } else if environment_mode.has_environment()
         && turn_context.model_info.apply_patch_tool_type.is_some()
     {
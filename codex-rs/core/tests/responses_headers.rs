@@ -89,6 +89,7 @@ async fn responses_stream_includes_subagent_header_on_review() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let codex_home = TempDir::new().expect("failed to create TempDir");
@@ -224,6 +225,7 @@ async fn responses_stream_includes_subagent_header_on_other() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let codex_home = TempDir::new().expect("failed to create TempDir");
@@ -345,6 +347,7 @@ async fn responses_stream_includes_thread_spawn_headers() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let codex_home = TempDir::new().expect("failed to create TempDir");
@@ -483,6 +486,7 @@ async fn responses_respects_model_info_overrides_from_config() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let codex_home = TempDir::new().expect("failed to create TempDir");
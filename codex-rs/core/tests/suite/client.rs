@@ -1295,6 +1295,7 @@ async fn send_provider_auth_request(server: &MockServer, auth: ModelProviderAuth
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let codex_home = TempDir::new().unwrap();
@@ -2909,6 +2910,7 @@ async fn azure_responses_request_includes_store_and_reasoning_ids() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let codex_home = TempDir::new().unwrap();
@@ -3531,6 +3533,7 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     // Init session
@@ -3621,6 +3624,7 @@ async fn env_var_overrides_loaded_auth() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     // Init session
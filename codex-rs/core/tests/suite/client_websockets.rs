@@ -2120,6 +2120,7 @@ fn websocket_provider_with_connect_timeout(
         requires_openai_auth: false,
         supports_websockets: true,
         system_role: None,
+        compat: None,
     }
 }
 
@@ -109,6 +109,7 @@ mod rollout_list_find;
 mod safety_buffering;
 mod safety_check_downgrade;
 mod search_tool;
+mod served_by;
 mod shell_command;
 mod shell_serialization;
 mod shell_snapshot;
@@ -0,0 +1,101 @@
+use anyhow::Ok;
+use codex_model_provider_info::ModelProviderInfo;
+use codex_model_provider_info::WireApi;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::Op;
+use codex_protocol::protocol::ServedByEvent;
+use codex_protocol::user_input::UserInput;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const PROVIDER_ID: &str = "mock-chat";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn chat_turn_reports_serving_provider_and_model() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    let delta = json!({ "choices": [{ "delta": { "content": "hi" } }] });
+    let finish = json!({ "choices": [{ "delta": {}, "finish_reason": "stop" }] });
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            format!("data: {delta}\n\ndata: {finish}\n\ndata: [DONE]\n\n"),
+            "text/event-stream",
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // The display name differs from the `model_providers` key so the test
+    // pins that clients see the key.
+    let provider = ModelProviderInfo {
+        name: "Mock Chat".into(),
+        base_url: Some(format!("{}/v1", server.uri())),
+        env_key: Some("PATH".into()),
+        env_key_instructions: None,
+        experimental_bearer_token: None,
+        auth: None,
+        aws: None,
+        wire_api: WireApi::Chat,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(2_000),
+        websocket_connect_timeout_ms: None,
+        requires_openai_auth: false,
+        supports_websockets: false,
+        system_role: None,
+        compat: None,
+    };
+    let test = test_codex()
+        .with_config(move |config| {
+            config.model_provider_id = PROVIDER_ID.to_string();
+            config.model_provider = provider;
+        })
+        .build(&server)
+        .await?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+            responsesapi_client_metadata: None,
+            additional_context: Default::default(),
+            thread_settings: Default::default(),
+        })
+        .await?;
+
+    let event = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::ServedBy(event) => Some(event.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(
+        event,
+        ServedByEvent {
+            provider: PROVIDER_ID.to_string(),
+            model: test.session_configured.model.clone(),
+        }
+    );
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
+
+    Ok(())
+}
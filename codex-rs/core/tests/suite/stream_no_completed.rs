@@ -65,6 +65,7 @@ async fn retries_on_early_close() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let TestCodex { codex, .. } = test_codex()
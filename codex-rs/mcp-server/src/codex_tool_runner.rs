@@ -267,6 +267,7 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::GuardianWarning(_)
                     | EventMsg::ModelVerification(_)
                     | EventMsg::SafetyBuffering(_)
+                    | EventMsg::ServedBy(_)
                     | EventMsg::TurnModerationMetadata(_) => {
                         continue;
                     }
@@ -5,6 +5,13 @@
 /// and maintain.
 use std::collections::HashMap;
 
+use codex_api::ProviderCompat as ApiProviderCompat;
+use codex_api::ReasoningEffortSupport;
+use codex_api::ReasoningTags;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
 use crate::ModelProviderInfo;
 use crate::WireApi;
 
@@ -12,6 +19,169 @@ pub const OPENROUTER_PROVIDER_ID: &str = "openrouter";
 pub const MINIMAX_PROVIDER_ID: &str = "minimax";
 pub const ZHIPU_PROVIDER_ID: &str = "zhipu";
 
+/// Per-provider overrides for OpenAI-compatible gateways whose behavior
+/// differs from the reference API. Every field is optional; unset fields keep
+/// the default behavior.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ProviderCompatInfo {
+    /// Emit function tools as strict schemas (`strict: true`, every property
+    /// listed in `required`, optional properties nullable). Some providers
+    /// only call tools reliably when the schema is strict.
+    pub strict_tool_schemas: Option<bool>,
+    /// Whether the Chat Completions endpoint supports `stream: true`. When
+    /// false, requests are sent non-streaming and the JSON response is
+    /// adapted into the usual event stream. Defaults to true.
+    pub supports_streaming: Option<bool>,
+    /// Treat `finish_reason: "tool_calls"` as the end of the Chat Completions
+    /// stream, for providers that omit the trailing `[DONE]`. Defaults to
+    /// false.
+    pub tool_calls_finish_terminal: Option<bool>,
+    /// Replace Chat Completions tool-call ids with synthetic ids unique across
+    /// turns, for providers whose ids are empty or reused. Defaults to false.
+    pub synthesize_tool_call_ids: Option<bool>,
+    /// Repair Chat Completions tool-call arguments that are not valid JSON
+    /// (trailing commas, single-quoted strings) before the call is run.
+    /// Defaults to false.
+    pub repair_tool_call_arguments: Option<bool>,
+    /// Send `tool_stream: true` so tool-call arguments are streamed. Defaults
+    /// to true for Zhipu and false otherwise.
+    pub tool_stream: Option<bool>,
+    /// How reasoning effort is sent: `levels` as `reasoning_effort`, `toggle`
+    /// as `thinking` enabled/disabled, or `unsupported` to omit it. Defaults
+    /// to `toggle` for Zhipu and `unsupported` otherwise.
+    pub reasoning_effort: Option<ReasoningEffortSupport>,
+    /// Tags around reasoning inlined in assistant content (`think`,
+    /// `thinking` or `reasoning`). The reasoning is split out of the reply.
+    /// Defaults to `thinking` for DeepSeek; otherwise tags stay inline.
+    pub reasoning_tags: Option<ReasoningTags>,
+    /// Maximum number of concurrent requests to this provider, shared by the
+    /// main turn and any sub-agents. Requests beyond the limit queue. Unset
+    /// means no limit.
+    #[schemars(range(min = 1))]
+    pub max_concurrent_requests: Option<usize>,
+    /// Maximum number of tool calls per assistant message in Chat Completions
+    /// history, for providers that cap parallel calls. Larger messages are
+    /// split into consecutive assistant messages. Unset means no limit.
+    #[schemars(range(min = 1))]
+    pub max_tool_calls_per_message: Option<usize>,
+    /// Retry HTTP 429 responses with backoff, up to `request_max_retries`.
+    /// Other 4xx responses are never retried. Defaults to false.
+    pub retry_429: Option<bool>,
+}
+
+impl ProviderCompatInfo {
+    /// Overlays every setting present in `configured` onto `self`.
+    fn merge(&mut self, configured: ProviderCompatInfo) {
+        let ProviderCompatInfo {
+            strict_tool_schemas,
+            supports_streaming,
+            tool_calls_finish_terminal,
+            synthesize_tool_call_ids,
+            repair_tool_call_arguments,
+            tool_stream,
+            reasoning_effort,
+            reasoning_tags,
+            max_concurrent_requests,
+            max_tool_calls_per_message,
+            retry_429,
+        } = configured;
+        self.strict_tool_schemas = strict_tool_schemas.or(self.strict_tool_schemas);
+        self.supports_streaming = supports_streaming.or(self.supports_streaming);
+        self.tool_calls_finish_terminal =
+            tool_calls_finish_terminal.or(self.tool_calls_finish_terminal);
+        self.synthesize_tool_call_ids = synthesize_tool_call_ids.or(self.synthesize_tool_call_ids);
+        self.repair_tool_call_arguments =
+            repair_tool_call_arguments.or(self.repair_tool_call_arguments);
+        self.tool_stream = tool_stream.or(self.tool_stream);
+        self.reasoning_effort = reasoning_effort.or(self.reasoning_effort);
+        self.reasoning_tags = reasoning_tags.or(self.reasoning_tags);
+        self.max_concurrent_requests = max_concurrent_requests.or(self.max_concurrent_requests);
+        self.max_tool_calls_per_message =
+            max_tool_calls_per_message.or(self.max_tool_calls_per_message);
+        self.retry_429 = retry_429.or(self.retry_429);
+    }
+}
+
+impl ModelProviderInfo {
+    /// Whether function tool schemas should be emitted in strict form.
+    pub fn prefers_strict_tool_schemas(&self) -> bool {
+        self.compat
+            .as_ref()
+            .and_then(|compat| compat.strict_tool_schemas)
+            .unwrap_or(false)
+    }
+
+    /// Whether HTTP 429 responses should be retried with backoff.
+    pub fn retry_429(&self) -> bool {
+        self.compat
+            .as_ref()
+            .and_then(|compat| compat.retry_429)
+            .unwrap_or(false)
+    }
+
+    /// Resolves the compat overrides into the runtime form used by `codex-api`.
+    pub(crate) fn api_compat(&self) -> ApiProviderCompat {
+        let defaults = ApiProviderCompat::default();
+        let compat = self.compat.clone().unwrap_or_default();
+        let mut env_http_headers: Vec<(String, String)> = self
+            .env_http_headers
+            .iter()
+            .flatten()
+            .map(|(header, env_var)| (header.clone(), env_var.clone()))
+            .collect();
+        env_http_headers.sort();
+        ApiProviderCompat {
+            supports_streaming: compat
+                .supports_streaming
+                .unwrap_or(defaults.supports_streaming),
+            tool_calls_finish_terminal: compat
+                .tool_calls_finish_terminal
+                .unwrap_or(defaults.tool_calls_finish_terminal),
+            synthesize_tool_call_ids: compat
+                .synthesize_tool_call_ids
+                .unwrap_or(defaults.synthesize_tool_call_ids),
+            repair_tool_call_arguments: compat
+                .repair_tool_call_arguments
+                .unwrap_or(defaults.repair_tool_call_arguments),
+            tool_stream: compat.tool_stream,
+            reasoning_effort: compat.reasoning_effort,
+            reasoning_tags: compat.reasoning_tags,
+            max_concurrent_requests: compat.max_concurrent_requests,
+            max_tool_calls_per_message: compat.max_tool_calls_per_message,
+            env_http_headers,
+        }
+    }
+
+    /// Applies the parts of a user-configured `[model_providers.<id>]` entry
+    /// that may extend a built-in fork provider. Built-in providers otherwise
+    /// take precedence over configured entries with the same id.
+    pub(crate) fn apply_configured_fork_overrides(&mut self, configured: ModelProviderInfo) {
+        if let Some(configured_env_headers) = configured.env_http_headers {
+            self.env_http_headers
+                .get_or_insert_with(HashMap::new)
+                .extend(configured_env_headers);
+        }
+        if let Some(configured_query_params) = configured.query_params {
+            self.query_params
+                .get_or_insert_with(HashMap::new)
+                .extend(configured_query_params);
+        }
+        if let Some(configured_compat) = configured.compat {
+            self.compat
+                .get_or_insert_with(ProviderCompatInfo::default)
+                .merge(configured_compat);
+        }
+    }
+}
+
+pub(crate) fn is_fork_provider_id(id: &str) -> bool {
+    matches!(
+        id,
+        OPENROUTER_PROVIDER_ID | MINIMAX_PROVIDER_ID | ZHIPU_PROVIDER_ID
+    )
+}
+
 pub fn register_fork_providers(providers: &mut HashMap<String, ModelProviderInfo>) {
     providers.insert(OPENROUTER_PROVIDER_ID.into(), create_openrouter_provider());
     providers.insert(MINIMAX_PROVIDER_ID.into(), create_minimax_provider());
@@ -48,6 +218,7 @@ fn create_openrouter_provider() -> ModelProviderInfo {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     }
 }
 
@@ -73,6 +244,7 @@ fn create_zhipu_provider() -> ModelProviderInfo {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     }
 }
 
@@ -96,5 +268,10 @@ fn create_minimax_provider() -> ModelProviderInfo {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: Some("user".to_string()),
+        compat: Some(ProviderCompatInfo {
+            retry_429: Some(true),
+            synthesize_tool_call_ids: Some(true),
+            ..Default::default()
+        }),
     }
 }
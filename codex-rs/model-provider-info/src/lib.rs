@@ -25,6 +25,8 @@ use std::time::Duration;
 
 mod fork_providers;
 
+pub use fork_providers::ProviderCompatInfo;
+
 const DEFAULT_STREAM_IDLE_TIMEOUT_MS: u64 = 300_000;
 const DEFAULT_STREAM_MAX_RETRIES: u64 = 5;
 const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
@@ -151,6 +153,9 @@ pub struct ModelProviderInfo {
     /// completions. Most providers use `"system"` (the default). Set this if
     /// the provider expects a different role name.
     pub system_role: Option<String>,
+    /// Fork: behavior overrides for OpenAI-compatible providers that deviate
+    /// from the reference API.
+    pub compat: Option<ProviderCompatInfo>,
 }
 
 /// AWS SigV4 auth configuration for a model provider.
@@ -225,8 +230,7 @@ impl ModelProviderInfo {
     }
 
     fn build_header_map(&self) -> CodexResult<HeaderMap> {
-        let capacity = self.http_headers.as_ref().map_or(0, HashMap::len)
-            + self.env_http_headers.as_ref().map_or(0, HashMap::len);
+        let capacity = self.http_headers.as_ref().map_or(0, HashMap::len);
         let mut headers = HeaderMap::with_capacity(capacity);
         if let Some(extra) = &self.http_headers {
             for (k, v) in extra {
@@ -236,18 +240,8 @@ impl ModelProviderInfo {
             }
         }
 
-        if let Some(env_headers) = &self.env_http_headers {
-            for (header, env_var) in env_headers {
-                if let Ok(val) = std::env::var(env_var)
-                    && !val.trim().is_empty()
-                    && let (Ok(name), Ok(value)) =
-                        (HeaderName::try_from(header), HeaderValue::try_from(val))
-                {
-                    headers.insert(name, value);
-                }
-            }
-        }
-
+        // Fork: `env_http_headers` are resolved per request by codex-api (see
+        // `api_compat`), so they are not baked into the static header map.
         Ok(headers)
     }
 
@@ -274,7 +268,8 @@ impl ModelProviderInfo {
         let retry = ApiRetryConfig {
             max_attempts: self.request_max_retries(),
             base_delay: Duration::from_millis(200),
-            retry_429: false,
+            // Fork: providers opt into 429 backoff via `compat.retry_429`.
+            retry_429: self.retry_429(),
             retry_5xx: true,
             retry_transport: true,
         };
@@ -287,6 +282,7 @@ impl ModelProviderInfo {
             retry,
             stream_idle_timeout: self.stream_idle_timeout(),
             system_role: self.system_role.clone(),
+            compat: self.api_compat(),
         })
     }
 
@@ -374,6 +370,7 @@ impl ModelProviderInfo {
             requires_openai_auth: true,
             supports_websockets: true,
             system_role: None,
+            compat: None,
         }
     }
 
@@ -405,6 +402,7 @@ impl ModelProviderInfo {
             requires_openai_auth: false,
             supports_websockets: false,
             system_role: None,
+            compat: None,
         }
     }
 
@@ -478,6 +476,13 @@ pub fn built_in_model_providers(
 /// Configured providers extend the built-in set. Built-in providers are not
 /// generally overridable, but the built-in Amazon Bedrock provider allows the
 /// user to set `aws.profile` and `aws.region`.
+///
+/// Fork: an entry with the id of a built-in fork provider is merged into it
+/// via `ModelProviderInfo::apply_configured_fork_overrides`:
+/// `env_http_headers` and `query_params` are added (configured keys replace
+/// built-in ones with the same name), and each configured `compat` knob
+/// overrides the built-in knob. Every other field of the entry is ignored,
+/// so the built-in name, base URL, auth and wire API always win.
 pub fn merge_configured_model_providers(
     mut model_providers: HashMap<String, ModelProviderInfo>,
     configured_model_providers: HashMap<String, ModelProviderInfo>,
@@ -503,6 +508,11 @@ pub fn merge_configured_model_providers(
                     built_in_aws.region = Some(region);
                 }
             }
+        } else if fork_providers::is_fork_provider_id(&key)
+            && let Some(built_in_provider) = model_providers.get_mut(&key)
+        {
+            // Fork: configured entries extend built-in fork providers.
+            built_in_provider.apply_configured_fork_overrides(provider);
         } else {
             model_providers.entry(key).or_insert(provider);
         }
@@ -550,6 +560,7 @@ pub fn create_oss_provider_with_base_url(base_url: &str, wire_api: WireApi) -> M
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     }
 }
 
@@ -30,6 +30,7 @@ base_url = "http://localhost:11434/v1"
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -65,6 +66,7 @@ query_params = { api-version = "2025-04-01-preview" }
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -103,6 +105,7 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -172,6 +175,7 @@ fn test_supports_remote_compaction_for_azure_name() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     assert!(provider.supports_remote_compaction());
@@ -198,6 +202,7 @@ fn test_supports_remote_compaction_for_non_openai_non_azure_provider() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
 
     assert!(!provider.supports_remote_compaction());
@@ -307,6 +312,7 @@ fn test_create_amazon_bedrock_provider() {
             requires_openai_auth: false,
             supports_websockets: false,
             system_role: None,
+            compat: None,
         }
     );
 }
@@ -527,6 +533,7 @@ fn detects_azure_responses_base_urls() {
             requires_openai_auth: false,
             supports_websockets: false,
             system_role: None,
+            compat: None,
         };
         let api = provider.to_api_provider(None).expect("api provider");
         assert!(
@@ -554,6 +561,7 @@ fn detects_azure_responses_base_urls() {
         requires_openai_auth: false,
         supports_websockets: false,
         system_role: None,
+        compat: None,
     };
     let named_api = named_provider.to_api_provider(None).expect("api provider");
     assert!(named_api.is_azure_responses_endpoint());
@@ -583,6 +591,7 @@ fn detects_azure_responses_base_urls() {
             requires_openai_auth: false,
             supports_websockets: false,
             system_role: None,
+            compat: None,
         };
         let api = provider.to_api_provider(None).expect("api provider");
         assert!(
@@ -591,3 +600,189 @@ fn detects_azure_responses_base_urls() {
         );
     }
 }
+
+#[test]
+fn test_retry_429_follows_provider_compat() {
+    let providers = built_in_model_providers(/*openai_base_url*/ None);
+    let minimax = providers
+        .get(fork_providers::MINIMAX_PROVIDER_ID)
+        .expect("minimax provider")
+        .to_api_provider(None)
+        .expect("api provider");
+    assert!(minimax.retry.retry_429);
+    assert!(minimax.retry.retry_5xx);
+
+    let provider: ModelProviderInfo = toml::from_str(
+        r#"
+name = "Example"
+base_url = "https://example.com/v1"
+"#,
+    )
+    .expect("provider should deserialize");
+    assert!(
+        !provider
+            .to_api_provider(None)
+            .expect("api provider")
+            .retry
+            .retry_429
+    );
+}
+
+#[test]
+fn test_merge_configured_model_providers_extends_fork_provider_env_headers() {
+    let configured_minimax = ModelProviderInfo {
+        name: "MiniMax".to_string(),
+        env_http_headers: Some(HashMap::from([(
+            "X-Project-Token".to_string(),
+            "MINIMAX_PROJECT_TOKEN".to_string(),
+        )])),
+        ..ModelProviderInfo::default()
+    };
+    let configured_model_providers = HashMap::from([(
+        fork_providers::MINIMAX_PROVIDER_ID.to_string(),
+        configured_minimax,
+    )]);
+
+    let mut expected = built_in_model_providers(/*openai_base_url*/ None);
+    expected
+        .get_mut(fork_providers::MINIMAX_PROVIDER_ID)
+        .expect("minimax provider")
+        .env_http_headers = Some(HashMap::from([(
+        "X-Project-Token".to_string(),
+        "MINIMAX_PROJECT_TOKEN".to_string(),
+    )]));
+
+    let merged = merge_configured_model_providers(
+        built_in_model_providers(/*openai_base_url*/ None),
+        configured_model_providers,
+    );
+    assert_eq!(merged, Ok(expected));
+
+    let api_provider = merged.expect("merged providers")[fork_providers::MINIMAX_PROVIDER_ID]
+        .to_api_provider(None)
+        .expect("api provider");
+    assert_eq!(
+        api_provider.compat.env_http_headers,
+        vec![(
+            "X-Project-Token".to_string(),
+            "MINIMAX_PROJECT_TOKEN".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_to_api_provider_resolves_env_http_headers_per_request() {
+    const ENV_VAR: &str = "CODEX_MODEL_PROVIDER_INFO_TEST_PROJECT_TOKEN";
+    let provider = ModelProviderInfo {
+        name: "Example".to_string(),
+        base_url: Some("https://example.com/v1".to_string()),
+        env_http_headers: Some(HashMap::from([(
+            "X-Project-Token".to_string(),
+            ENV_VAR.to_string(),
+        )])),
+        ..ModelProviderInfo::default()
+    };
+    let header = |api_provider: &ApiProvider| {
+        api_provider
+            .build_request(http::Method::POST, "chat/completions")
+            .headers
+            .get("X-Project-Token")
+            .map(|value| value.to_str().expect("ascii header").to_string())
+    };
+
+    // SAFETY: the variable name is unique to this test.
+    unsafe { std::env::set_var(ENV_VAR, "token-1") };
+    let api_provider = provider.to_api_provider(None).expect("api provider");
+    assert_eq!(api_provider.headers.get("X-Project-Token"), None);
+    assert_eq!(header(&api_provider), Some("token-1".to_string()));
+
+    unsafe { std::env::remove_var(ENV_VAR) };
+    assert_eq!(header(&api_provider), None);
+}
+
+#[test]
+fn test_merge_configured_model_providers_sets_fork_provider_concurrency_limit() {
+    let configured_minimax = ModelProviderInfo {
+        name: "MiniMax".to_string(),
+        compat: Some(ProviderCompatInfo {
+            max_concurrent_requests: Some(2),
+            ..Default::default()
+        }),
+        ..ModelProviderInfo::default()
+    };
+    let merged = merge_configured_model_providers(
+        built_in_model_providers(/*openai_base_url*/ None),
+        HashMap::from([(
+            fork_providers::MINIMAX_PROVIDER_ID.to_string(),
+            configured_minimax,
+        )]),
+    )
+    .expect("merged providers");
+
+    let api_provider = merged[fork_providers::MINIMAX_PROVIDER_ID]
+        .to_api_provider(None)
+        .expect("api provider");
+    assert_eq!(api_provider.compat.max_concurrent_requests, Some(2));
+    // Built-in compat settings are kept alongside the configured limit.
+    assert!(api_provider.retry.retry_429);
+}
+
+#[test]
+fn test_merge_configured_model_providers_overrides_fork_provider_compat() {
+    let configured_zhipu: ModelProviderInfo = toml::from_str(
+        r#"
+name = "Zhipu"
+
+[compat]
+tool_stream = false
+"#,
+    )
+    .expect("configured provider");
+    let merged = merge_configured_model_providers(
+        built_in_model_providers(/*openai_base_url*/ None),
+        HashMap::from([(
+            fork_providers::ZHIPU_PROVIDER_ID.to_string(),
+            configured_zhipu,
+        )]),
+    )
+    .expect("merged providers");
+
+    let api_provider = merged[fork_providers::ZHIPU_PROVIDER_ID]
+        .to_api_provider(None)
+        .expect("api provider");
+    assert!(!api_provider.sends_tool_stream());
+    // Unset knobs keep the built-in Zhipu defaults.
+    assert_eq!(
+        api_provider.reasoning_effort_support(),
+        codex_api::ReasoningEffortSupport::Toggle
+    );
+}
+
+#[test]
+fn test_merge_configured_model_providers_extends_fork_provider_query_params() {
+    let configured_zhipu = ModelProviderInfo {
+        name: "Zhipu".to_string(),
+        query_params: Some(HashMap::from([(
+            "api-version".to_string(),
+            "2024-06-01".to_string(),
+        )])),
+        ..ModelProviderInfo::default()
+    };
+    let merged = merge_configured_model_providers(
+        built_in_model_providers(/*openai_base_url*/ None),
+        HashMap::from([(
+            fork_providers::ZHIPU_PROVIDER_ID.to_string(),
+            configured_zhipu,
+        )]),
+    )
+    .expect("merged providers");
+
+    let api_provider = merged[fork_providers::ZHIPU_PROVIDER_ID]
+        .to_api_provider(None)
+        .expect("api provider");
+    let url = api_provider.url_for_path("chat/completions");
+    assert!(
+        url.ends_with("/chat/completions?api-version=2024-06-01"),
+        "{url}"
+    );
+}
@@ -15,6 +15,7 @@ use codex_login::AuthManager;
 use codex_login::CodexAuth;
 use codex_login::collect_auth_env_telemetry;
 use codex_login::default_client::build_reqwest_client;
+use codex_login::default_client::build_reqwest_client_for_url;
 use codex_model_provider_info::ModelProviderInfo;
 use codex_models_manager::manager::ModelsEndpointClient;
 use codex_models_manager::manager::ModelsEndpointFuture;
@@ -101,6 +102,31 @@ impl OpenAiModelsEndpoint {
         .map_err(map_api_error)
     }
 
+    /// Fork: fetches the remote fork model catalog. The catalog is public
+    /// metadata, so no provider auth is attached.
+    async fn fetch_fork_catalog(&self, url: &str) -> CoreResult<Vec<u8>> {
+        let request = async {
+            let response = build_reqwest_client_for_url(url)
+                .get(url)
+                .send()
+                .await
+                .map_err(|err| std::io::Error::other(format!("request failed: {err}")))?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(std::io::Error::other(format!("unexpected status {status}")));
+            }
+            let body = response
+                .bytes()
+                .await
+                .map_err(|err| std::io::Error::other(format!("failed to read body: {err}")))?;
+            Ok(body.to_vec())
+        };
+        timeout(MODELS_REFRESH_TIMEOUT, request)
+            .await
+            .map_err(|_| CodexErr::Timeout)?
+            .map_err(CodexErr::Io)
+    }
+
     fn auth_env(&self) -> AuthEnvTelemetry {
         let codex_api_key_env_enabled = self
             .auth_manager
@@ -125,6 +151,13 @@ impl ModelsEndpointClient for OpenAiModelsEndpoint {
     ) -> ModelsEndpointFuture<'a, CoreResult<(Vec<ModelInfo>, Option<String>)>> {
         Box::pin(OpenAiModelsEndpoint::list_models(self, client_version))
     }
+
+    fn fetch_fork_catalog<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> ModelsEndpointFuture<'a, CoreResult<Vec<u8>>> {
+        Box::pin(OpenAiModelsEndpoint::fetch_fork_catalog(self, url))
+    }
 }
 
 #[derive(Clone)]
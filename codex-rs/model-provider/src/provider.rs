@@ -400,6 +400,7 @@ mod tests {
             requires_openai_auth: false,
             supports_websockets: false,
             system_role: None,
+            compat: None,
         }
     }
 
@@ -18,15 +18,31 @@ use codex_protocol::openai_models::WebSearchToolType;
 const BASE_INSTRUCTIONS_WITH_TEXT_EDITOR: &str =
     include_str!("../../core/prompt_with_text_editor_instructions.md");
 
+/// Appends fork models missing from `models` and returns the catalog sorted by
+/// `(priority, slug)`.
+///
+/// Ties on `priority` are broken by `slug` so the merged list (and the picker
+/// built from it) is identical across runs regardless of input order.
 pub(crate) fn merge_with_fork_models(mut models: Vec<ModelInfo>) -> Vec<ModelInfo> {
     for model in fork_models() {
         if !models.iter().any(|existing| existing.slug == model.slug) {
             models.push(model);
         }
     }
+    models.sort_by(|a, b| (a.priority, &a.slug).cmp(&(b.priority, &b.slug)));
     models
 }
 
+/// Whether `slug` names one of the hardcoded fork models.
+pub(crate) fn is_fork_model_slug(slug: &str) -> bool {
+    fork_model(slug).is_some()
+}
+
+/// The hardcoded metadata for the fork model `slug`.
+pub(crate) fn fork_model(slug: &str) -> Option<ModelInfo> {
+    fork_models().into_iter().find(|model| model.slug == slug)
+}
+
 fn fork_models() -> Vec<ModelInfo> {
     vec![
         minimax_model("codex-MiniMax-M2.1", 200_000, 200),
@@ -166,4 +182,35 @@ mod tests {
             .count();
         assert_eq!(count, 1);
     }
+
+    #[test]
+    fn merge_orders_by_priority_then_slug() {
+        let existing = vec![
+            zhipu_model("zeta", 128_000, 5),
+            zhipu_model("alpha", 128_000, 5),
+            zhipu_model("first", 128_000, 1),
+        ];
+        let slugs = |models: Vec<ModelInfo>| -> Vec<String> {
+            models.into_iter().map(|model| model.slug).collect()
+        };
+
+        let merged = slugs(merge_with_fork_models(existing.clone()));
+        let mut reversed = existing.clone();
+        reversed.reverse();
+
+        assert_eq!(
+            merged,
+            vec![
+                "first".to_string(),
+                "alpha".to_string(),
+                "zeta".to_string(),
+                "codex-MiniMax-M2.1".to_string(),
+                "MiniMax-M2.5".to_string(),
+                "glm-5".to_string(),
+                "glm-4.7".to_string(),
+            ]
+        );
+        assert_eq!(slugs(merge_with_fork_models(existing)), merged);
+        assert_eq!(slugs(merge_with_fork_models(reversed)), merged);
+    }
 }
@@ -0,0 +1,343 @@
+//! Fork: remote overrides for fork model metadata.
+//!
+//! Fork models are hardcoded in `fork_catalog.rs`. When
+//! `CODEX_FORK_MODELS_URL` is set, a `models.json`-style document is fetched
+//! from that URL and its entries override the metadata of matching fork
+//! models, so provider changes (context windows, reasoning presets,
+//! descriptions) do not require a release. The last good document is cached
+//! under `codex_home` and used until a fetch succeeds; without one the
+//! hardcoded defaults apply.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::openai_models::ReasoningEffortPreset;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tracing::info;
+use tracing::warn;
+
+use crate::fork_catalog::fork_model;
+use crate::fork_catalog::is_fork_model_slug;
+use crate::manager::ModelsEndpointClient;
+
+/// Environment variable naming the URL of the remote fork model catalog.
+///
+/// Deliberately env-only while the document format is experimental; there is
+/// no `config.toml` key.
+pub const FORK_MODELS_URL_ENV_VAR: &str = "CODEX_FORK_MODELS_URL";
+
+const FORK_MODELS_CACHE_FILE: &str = "fork_models_cache.json";
+
+/// How long to wait after a failed fetch before trying again.
+const FORK_MODELS_RETRY_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// Remote fork model catalog, in the `{"models": [...]}` shape of
+/// `models.json` but limited to the fields fork models let providers change.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ForkCatalogOverrides {
+    pub models: Vec<ForkModelOverride>,
+}
+
+/// Metadata override for one fork model. Unset fields keep the hardcoded
+/// value.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ForkModelOverride {
+    pub slug: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_reasoning_level: Option<ReasoningEffort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_reasoning_levels: Option<Vec<ReasoningEffortPreset>>,
+}
+
+impl ForkCatalogOverrides {
+    /// Parses and validates a remote catalog. Any invalid entry rejects the
+    /// whole document so a bad publish cannot half-apply.
+    pub fn parse(body: &[u8]) -> Result<Self, String> {
+        let catalog: Self = serde_json::from_slice(body)
+            .map_err(|err| format!("invalid fork model catalog: {err}"))?;
+        for model in &catalog.models {
+            model.validate()?;
+        }
+        Ok(catalog)
+    }
+
+    /// Applies the overrides to the matching fork models in `models`.
+    pub(crate) fn apply(&self, models: &mut [ModelInfo]) {
+        for model in models
+            .iter_mut()
+            .filter(|model| is_fork_model_slug(&model.slug))
+        {
+            if let Some(entry) = self.models.iter().find(|entry| entry.slug == model.slug) {
+                entry.apply(model);
+            }
+        }
+    }
+}
+
+impl ForkModelOverride {
+    fn validate(&self) -> Result<(), String> {
+        let slug = &self.slug;
+        let Some(model) = fork_model(slug) else {
+            return Err(format!(
+                "fork model catalog entry {slug:?} is not a fork model"
+            ));
+        };
+        if let Some(context_window) = self.context_window
+            && context_window <= 0
+        {
+            return Err(format!(
+                "fork model catalog entry {slug:?} has non-positive context_window {context_window}"
+            ));
+        }
+        if self
+            .supported_reasoning_levels
+            .as_ref()
+            .is_some_and(Vec::is_empty)
+        {
+            return Err(format!(
+                "fork model catalog entry {slug:?} has no supported_reasoning_levels"
+            ));
+        }
+        // Check the default and levels the model ends up with after the
+        // override, so overriding either one alone cannot leave them disagreeing.
+        let levels = self
+            .supported_reasoning_levels
+            .as_ref()
+            .unwrap_or(&model.supported_reasoning_levels);
+        if let Some(default) = self
+            .default_reasoning_level
+            .as_ref()
+            .or(model.default_reasoning_level.as_ref())
+            && !levels.iter().any(|level| &level.effort == default)
+        {
+            return Err(format!(
+                "fork model catalog entry {slug:?} defaults to unsupported reasoning level {default}"
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, model: &mut ModelInfo) {
+        if let Some(context_window) = self.context_window {
+            model.context_window = Some(context_window);
+            model.max_context_window = Some(context_window);
+        }
+        if let Some(description) = &self.description {
+            model.description = Some(description.clone());
+        }
+        if let Some(levels) = &self.supported_reasoning_levels {
+            model.supported_reasoning_levels = levels.clone();
+        }
+        if let Some(default) = &self.default_reasoning_level {
+            model.default_reasoning_level = Some(default.clone());
+        }
+    }
+}
+
+/// Current fork catalog overrides plus where to refresh and cache them.
+#[derive(Debug)]
+pub(crate) struct RemoteForkCatalog {
+    url: Option<String>,
+    cache_path: PathBuf,
+    overrides: RwLock<Option<ForkCatalogOverrides>>,
+    fetch_state: Mutex<FetchState>,
+    retry_backoff: Duration,
+}
+
+#[derive(Debug, Default)]
+struct FetchState {
+    fetched: bool,
+    retry_at: Option<Instant>,
+}
+
+impl RemoteForkCatalog {
+    /// Creates the catalog for `url`, seeded from the cache in `codex_home`.
+    pub(crate) fn new(url: Option<String>, codex_home: &Path) -> Self {
+        let cache_path = codex_home.join(FORK_MODELS_CACHE_FILE);
+        let overrides = url.as_ref().and_then(|_| load_cache(&cache_path));
+        Self {
+            url,
+            cache_path,
+            overrides: RwLock::new(overrides),
+            fetch_state: Mutex::new(FetchState::default()),
+            retry_backoff: FORK_MODELS_RETRY_BACKOFF,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_retry_backoff(&mut self, retry_backoff: Duration) {
+        self.retry_backoff = retry_backoff;
+    }
+
+    /// Applies the current overrides, if any, to `models`.
+    pub(crate) fn apply(&self, models: &mut [ModelInfo]) {
+        if let Some(overrides) = self
+            .overrides
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            overrides.apply(models);
+        }
+    }
+
+    /// Fetches the remote catalog until one fetch succeeds, waiting
+    /// `retry_backoff` after each failure. Returns whether new overrides were
+    /// stored; on failure the cached or hardcoded metadata stays in effect.
+    pub(crate) async fn refresh(&self, endpoint: &dyn ModelsEndpointClient) -> bool {
+        let Some(url) = self.url.as_deref() else {
+            return false;
+        };
+        // Held across the fetch so concurrent refreshes do not fetch twice.
+        let mut state = self.fetch_state.lock().await;
+        if state.fetched
+            || state
+                .retry_at
+                .is_some_and(|retry_at| Instant::now() < retry_at)
+        {
+            return false;
+        }
+        let overrides = match endpoint.fetch_fork_catalog(url).await {
+            Ok(body) => ForkCatalogOverrides::parse(&body),
+            Err(err) => Err(format!("failed to fetch fork model catalog: {err}")),
+        };
+        let overrides = match overrides {
+            Ok(overrides) => overrides,
+            Err(err) => {
+                warn!(url, "{err}; keeping cached or built-in fork model metadata");
+                state.retry_at = Some(Instant::now() + self.retry_backoff);
+                return false;
+            }
+        };
+        state.fetched = true;
+        persist_cache(&self.cache_path, &overrides).await;
+        info!(
+            url,
+            models_count = overrides.models.len(),
+            "fork model catalog refreshed"
+        );
+        *self
+            .overrides
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(overrides);
+        true
+    }
+}
+
+fn load_cache(path: &Path) -> Option<ForkCatalogOverrides> {
+    let body = std::fs::read(path).ok()?;
+    match ForkCatalogOverrides::parse(&body) {
+        Ok(overrides) => Some(overrides),
+        Err(err) => {
+            warn!(cache_path = %path.display(), "ignoring fork model catalog cache: {err}");
+            None
+        }
+    }
+}
+
+async fn persist_cache(path: &Path, overrides: &ForkCatalogOverrides) {
+    let body = match serde_json::to_vec_pretty(overrides) {
+        Ok(body) => body,
+        Err(err) => {
+            warn!("failed to serialize fork model catalog cache: {err}");
+            return;
+        }
+    };
+    if let Err(err) = tokio::fs::write(path, body).await {
+        warn!(cache_path = %path.display(), "failed to write fork model catalog cache: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_rejects_non_fork_models() {
+        let err = ForkCatalogOverrides::parse(br#"{"models":[{"slug":"gpt-5"}]}"#)
+            .expect_err("non-fork slug should be rejected");
+        assert_eq!(
+            err,
+            r#"fork model catalog entry "gpt-5" is not a fork model"#
+        );
+    }
+
+    #[test]
+    fn parse_rejects_non_positive_context_window() {
+        let err =
+            ForkCatalogOverrides::parse(br#"{"models":[{"slug":"glm-5","context_window":0}]}"#)
+                .expect_err("zero context window should be rejected");
+        assert_eq!(
+            err,
+            r#"fork model catalog entry "glm-5" has non-positive context_window 0"#
+        );
+    }
+
+    #[test]
+    fn parse_rejects_default_outside_hardcoded_levels() {
+        let err = ForkCatalogOverrides::parse(
+            br#"{"models":[{"slug":"glm-5","default_reasoning_level":"xhigh"}]}"#,
+        )
+        .expect_err("default outside the hardcoded levels should be rejected");
+        assert_eq!(
+            err,
+            r#"fork model catalog entry "glm-5" defaults to unsupported reasoning level xhigh"#
+        );
+        ForkCatalogOverrides::parse(
+            br#"{"models":[{"slug":"glm-5","default_reasoning_level":"high"}]}"#,
+        )
+        .expect("default within the hardcoded levels should parse");
+    }
+
+    #[test]
+    fn parse_rejects_levels_that_drop_the_hardcoded_default() {
+        let err = ForkCatalogOverrides::parse(
+            br#"{"models":[{"slug":"glm-5","supported_reasoning_levels":[{"effort":"high","description":"Deep"}]}]}"#,
+        )
+        .expect_err("levels without the hardcoded default should be rejected");
+        assert_eq!(
+            err,
+            r#"fork model catalog entry "glm-5" defaults to unsupported reasoning level medium"#
+        );
+    }
+
+    #[test]
+    fn apply_leaves_unset_fields_alone() {
+        let overrides = ForkCatalogOverrides::parse(
+            br#"{"models":[{"slug":"glm-5","description":"updated"}]}"#,
+        )
+        .expect("catalog should parse");
+        let mut models = crate::fork_catalog::merge_with_fork_models(Vec::new());
+        let before = models
+            .iter()
+            .find(|model| model.slug == "glm-5")
+            .cloned()
+            .expect("glm-5 should exist");
+
+        overrides.apply(&mut models);
+
+        let after = models
+            .iter()
+            .find(|model| model.slug == "glm-5")
+            .expect("glm-5 should exist");
+        assert_eq!(after.description.as_deref(), Some("updated"));
+        assert_eq!(after.context_window, before.context_window);
+        assert_eq!(
+            after.supported_reasoning_levels,
+            before.supported_reasoning_levels
+        );
+    }
+}
@@ -4,6 +4,7 @@ pub(crate) mod config;
 pub mod fork_catalog;
 pub mod fork_model_info;
 pub mod fork_provider_mapping;
+pub mod fork_remote_catalog;
 pub mod manager;
 pub mod model_info;
 pub mod model_presets;
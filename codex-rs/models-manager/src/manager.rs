@@ -1,10 +1,13 @@
 use super::cache::ModelsCacheManager;
 use crate::collaboration_mode_presets::builtin_collaboration_mode_presets;
 use crate::config::ModelsManagerConfig;
+use crate::fork_remote_catalog::FORK_MODELS_URL_ENV_VAR;
+use crate::fork_remote_catalog::RemoteForkCatalog;
 use crate::model_info;
 use codex_login::AuthManager;
 use codex_protocol::auth::AuthMode;
 use codex_protocol::config_types::CollaborationModeMask;
+use codex_protocol::error::CodexErr;
 use codex_protocol::error::Result as CoreResult;
 use codex_protocol::openai_models::ModelInfo;
 use codex_protocol::openai_models::ModelPreset;
@@ -42,6 +45,18 @@ pub trait ModelsEndpointClient: fmt::Debug + Send + Sync {
         &'a self,
         client_version: &'a str,
     ) -> ModelsEndpointFuture<'a, CoreResult<(Vec<ModelInfo>, Option<String>)>>;
+
+    /// Fork: fetches the raw body of the remote fork model catalog at `url`.
+    fn fetch_fork_catalog<'a>(
+        &'a self,
+        _url: &'a str,
+    ) -> ModelsEndpointFuture<'a, CoreResult<Vec<u8>>> {
+        Box::pin(async {
+            Err(CodexErr::UnsupportedOperation(
+                "fetching the fork model catalog".to_string(),
+            ))
+        })
+    }
 }
 
 pub type ModelsEndpointFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -115,7 +130,9 @@ pub trait ModelsManager: fmt::Debug + Send + Sync {
 
     /// Build picker-ready presets from the active catalog snapshot.
     fn build_available_models(&self, mut remote_models: Vec<ModelInfo>) -> Vec<ModelPreset> {
-        remote_models.sort_by_key(|model| model.priority);
+        // Fork: break priority ties by slug so the picker order does not depend
+        // on whether a model came from the bundled file or the server response.
+        remote_models.sort_by(|a, b| (a.priority, &a.slug).cmp(&(b.priority, &b.slug)));
 
         let mut presets: Vec<ModelPreset> = remote_models.into_iter().map(Into::into).collect();
         let uses_codex_backend = self
@@ -204,6 +221,8 @@ pub struct OpenAiModelsManager {
     cache_manager: ModelsCacheManager,
     endpoint_client: SharedModelsEndpointClient,
     auth_manager: Option<Arc<AuthManager>>,
+    /// Fork: remote overrides for fork model metadata.
+    fork_catalog: RemoteForkCatalog,
 }
 
 /// Static model manager backed by an authoritative in-process catalog.
@@ -219,16 +238,33 @@ impl OpenAiModelsManager {
         codex_home: PathBuf,
         endpoint_client: Arc<dyn ModelsEndpointClient>,
         auth_manager: Option<Arc<AuthManager>>,
+    ) -> Self {
+        let fork_catalog_url = std::env::var(FORK_MODELS_URL_ENV_VAR)
+            .ok()
+            .filter(|url| !url.trim().is_empty());
+        Self::with_fork_catalog_url(codex_home, endpoint_client, auth_manager, fork_catalog_url)
+    }
+
+    /// Fork: like [`Self::new`], with fork model metadata refreshed from
+    /// `fork_catalog_url` instead of `CODEX_FORK_MODELS_URL`.
+    pub fn with_fork_catalog_url(
+        codex_home: PathBuf,
+        endpoint_client: Arc<dyn ModelsEndpointClient>,
+        auth_manager: Option<Arc<AuthManager>>,
+        fork_catalog_url: Option<String>,
     ) -> Self {
         let cache_path = codex_home.join(MODEL_CACHE_FILE);
         let cache_manager = ModelsCacheManager::new(cache_path, DEFAULT_MODEL_CACHE_TTL);
-        let remote_models = load_remote_models_from_file().unwrap_or_default();
+        let fork_catalog = RemoteForkCatalog::new(fork_catalog_url, &codex_home);
+        let mut remote_models = load_remote_models_from_file().unwrap_or_default();
+        fork_catalog.apply(&mut remote_models);
         Self {
             remote_models: RwLock::new(remote_models),
             etag: RwLock::new(None),
             cache_manager,
             endpoint_client,
             auth_manager,
+            fork_catalog,
         }
     }
 }
@@ -300,6 +336,17 @@ impl OpenAiModelsManager {
 
     /// Refresh available models according to the specified strategy.
     async fn refresh_available_models(&self, refresh_strategy: RefreshStrategy) -> CoreResult<()> {
+        // Fork: fork model metadata refreshes independently of `/models`.
+        if refresh_strategy != RefreshStrategy::Offline
+            && self
+                .fork_catalog
+                .refresh(self.endpoint_client.as_ref())
+                .await
+        {
+            self.fork_catalog
+                .apply(&mut self.remote_models.write().await);
+        }
+
         if !self.should_refresh_models().await {
             if matches!(
                 refresh_strategy,
@@ -380,6 +427,7 @@ impl OpenAiModelsManager {
                 existing_models.push(model);
             }
         }
+        self.fork_catalog.apply(&mut existing_models);
         *self.remote_models.write().await = existing_models;
     }
 
@@ -77,6 +77,7 @@ struct TestModelsEndpoint {
     uses_codex_backend: bool,
     responses: Mutex<VecDeque<Vec<ModelInfo>>>,
     fetch_count: AtomicUsize,
+    fork_catalog: Mutex<Option<Result<Vec<u8>, String>>>,
 }
 
 impl TestModelsEndpoint {
@@ -86,6 +87,7 @@ impl TestModelsEndpoint {
             uses_codex_backend: true,
             responses: Mutex::new(responses.into()),
             fetch_count: AtomicUsize::new(0),
+            fork_catalog: Mutex::new(None),
         })
     }
 
@@ -95,9 +97,18 @@ impl TestModelsEndpoint {
             uses_codex_backend: false,
             responses: Mutex::new(responses.into()),
             fetch_count: AtomicUsize::new(0),
+            fork_catalog: Mutex::new(None),
         })
     }
 
+    fn with_fork_catalog(self: Arc<Self>, body: Result<Vec<u8>, String>) -> Arc<Self> {
+        *self
+            .fork_catalog
+            .lock()
+            .expect("fork catalog lock should not be poisoned") = Some(body);
+        self
+    }
+
     fn fetch_count(&self) -> usize {
         self.fetch_count.load(Ordering::SeqCst)
     }
@@ -173,6 +184,26 @@ impl ModelsEndpointClient for TestModelsEndpoint {
     ) -> ModelsEndpointFuture<'a, CoreResult<(Vec<ModelInfo>, Option<String>)>> {
         Box::pin(TestModelsEndpoint::list_models(self))
     }
+
+    fn fetch_fork_catalog<'a>(
+        &'a self,
+        _url: &'a str,
+    ) -> ModelsEndpointFuture<'a, CoreResult<Vec<u8>>> {
+        let body = self
+            .fork_catalog
+            .lock()
+            .expect("fork catalog lock should not be poisoned")
+            .take();
+        Box::pin(async move {
+            match body {
+                Some(Ok(body)) => Ok(body),
+                Some(Err(message)) => Err(CodexErr::Io(std::io::Error::other(message))),
+                None => Err(CodexErr::UnsupportedOperation(
+                    "no fork catalog configured".to_string(),
+                )),
+            }
+        })
+    }
 }
 
 fn openai_manager_for_tests(
@@ -475,6 +506,45 @@ async fn refresh_available_models_sorts_by_priority() {
     assert_eq!(endpoint.fetch_count(), 1, "expected a single model fetch");
 }
 
+#[tokio::test]
+async fn list_models_breaks_priority_ties_by_slug() {
+    let forward = vec![
+        remote_model("tie-zeta", "Zeta", /*priority*/ 0),
+        remote_model("tie-alpha", "Alpha", /*priority*/ 0),
+    ];
+    let mut reversed = forward.clone();
+    reversed.reverse();
+
+    let mut listed = Vec::new();
+    for remote_models in [forward, reversed] {
+        let codex_home = tempdir().expect("temp dir");
+        let endpoint = TestModelsEndpoint::new(vec![remote_models]);
+        let manager = openai_manager_for_tests(codex_home.path().to_path_buf(), endpoint);
+        manager
+            .refresh_available_models(RefreshStrategy::OnlineIfUncached)
+            .await
+            .expect("refresh succeeds");
+        let slugs: Vec<String> = manager
+            .list_models(RefreshStrategy::OnlineIfUncached)
+            .await
+            .into_iter()
+            .map(|preset| preset.model)
+            .collect();
+        listed.push(slugs);
+    }
+
+    let alpha_idx = listed[0]
+        .iter()
+        .position(|slug| slug == "tie-alpha")
+        .expect("tie-alpha should be listed");
+    let zeta_idx = listed[0]
+        .iter()
+        .position(|slug| slug == "tie-zeta")
+        .expect("tie-zeta should be listed");
+    assert!(alpha_idx < zeta_idx, "equal priorities should sort by slug");
+    assert_eq!(listed[0], listed[1]);
+}
+
 #[tokio::test]
 async fn refresh_available_models_uses_remote_only_catalog_for_chatgpt_auth() {
     let remote_models = vec![remote_model(
@@ -609,6 +679,7 @@ async fn refresh_available_models_keeps_merging_for_api_auth() {
         uses_codex_backend: false,
         responses: Mutex::new(vec![remote_models.clone()].into()),
         fetch_count: AtomicUsize::new(0),
+        fork_catalog: Mutex::new(None),
     });
     let manager = openai_manager_for_tests_with_auth(
         codex_home.path().to_path_buf(),
@@ -814,6 +885,7 @@ impl TestAuthAwareModelsEndpoint {
             auth_manager,
             responses: Mutex::new(responses.into()),
             fetch_count: AtomicUsize::new(0),
+            fork_catalog: Mutex::new(None),
         })
     }
 
@@ -1067,3 +1139,109 @@ fn bundled_catalog_includes_fork_models() {
         "bundled catalog should include glm-5"
     );
 }
+
+fn fork_manager_for_tests(
+    codex_home: &Path,
+    endpoint_client: Arc<dyn ModelsEndpointClient>,
+) -> OpenAiModelsManager {
+    OpenAiModelsManager::with_fork_catalog_url(
+        codex_home.to_path_buf(),
+        endpoint_client,
+        /*auth_manager*/ None,
+        Some("https://example.com/fork-models.json".to_string()),
+    )
+}
+
+#[tokio::test]
+async fn remote_fork_catalog_overrides_fork_model_metadata() {
+    let codex_home = tempdir().expect("temp dir");
+    let body = json!({
+        "models": [{
+            "slug": "glm-5",
+            "context_window": 256_000,
+            "description": "GLM-5 with a larger context window",
+        }]
+    });
+    let endpoint = TestModelsEndpoint::without_refresh(Vec::new())
+        .with_fork_catalog(Ok(serde_json::to_vec(&body).expect("serialize catalog")));
+    let manager = fork_manager_for_tests(codex_home.path(), endpoint);
+
+    manager
+        .refresh_available_models(RefreshStrategy::OnlineIfUncached)
+        .await
+        .expect("refresh succeeds");
+
+    let models = manager.get_remote_models().await;
+    let glm = models
+        .iter()
+        .find(|model| model.slug == "glm-5")
+        .expect("glm-5 should be listed");
+    assert_eq!(glm.context_window, Some(256_000));
+    assert_eq!(glm.max_context_window, Some(256_000));
+    assert_eq!(
+        glm.description.as_deref(),
+        Some("GLM-5 with a larger context window")
+    );
+    assert!(
+        codex_home.path().join("fork_models_cache.json").exists(),
+        "fetched catalog should be cached"
+    );
+
+    // A fresh manager starts from the cached catalog before any fetch.
+    let offline = fork_manager_for_tests(
+        codex_home.path(),
+        TestModelsEndpoint::without_refresh(Vec::new()),
+    );
+    let cached = offline.get_remote_models().await;
+    let glm = cached
+        .iter()
+        .find(|model| model.slug == "glm-5")
+        .expect("glm-5 should be listed");
+    assert_eq!(glm.context_window, Some(256_000));
+}
+
+#[tokio::test]
+async fn remote_fork_catalog_failure_keeps_builtin_metadata() {
+    let codex_home = tempdir().expect("temp dir");
+    let expected = load_remote_models_from_file().expect("bundled models should parse");
+    let endpoint = TestModelsEndpoint::without_refresh(Vec::new())
+        .with_fork_catalog(Err("connection refused".to_string()));
+    let manager = fork_manager_for_tests(codex_home.path(), endpoint);
+
+    manager
+        .refresh_available_models(RefreshStrategy::OnlineIfUncached)
+        .await
+        .expect("refresh succeeds");
+
+    assert_eq!(manager.get_remote_models().await, expected);
+    assert!(!codex_home.path().join("fork_models_cache.json").exists());
+}
+
+#[tokio::test]
+async fn remote_fork_catalog_retries_after_failed_fetch() {
+    let codex_home = tempdir().expect("temp dir");
+    let endpoint = TestModelsEndpoint::without_refresh(Vec::new())
+        .with_fork_catalog(Err("connection refused".to_string()));
+    let mut manager = fork_manager_for_tests(codex_home.path(), endpoint.clone());
+    manager.fork_catalog.set_retry_backoff(Duration::ZERO);
+
+    manager
+        .refresh_available_models(RefreshStrategy::OnlineIfUncached)
+        .await
+        .expect("refresh succeeds");
+    let body = json!({
+        "models": [{ "slug": "glm-5", "context_window": 256_000 }]
+    });
+    let _ = endpoint.with_fork_catalog(Ok(serde_json::to_vec(&body).expect("serialize catalog")));
+    manager
+        .refresh_available_models(RefreshStrategy::OnlineIfUncached)
+        .await
+        .expect("refresh succeeds");
+
+    let models = manager.get_remote_models().await;
+    let glm = models
+        .iter()
+        .find(|model| model.slug == "glm-5")
+        .expect("glm-5 should be listed");
+    assert_eq!(glm.context_window, Some(256_000));
+}
@@ -1306,6 +1306,9 @@ pub enum EventMsg {
     /// Backend indicates that response output is waiting on a safety review.
     SafetyBuffering(SafetyBufferingEvent),
 
+    /// Fork: the provider and model that served a model response.
+    ServedBy(ServedByEvent),
+
     /// Conversation history was compacted (either automatically or manually).
     ContextCompacted(ContextCompactedEvent),
 
@@ -1995,6 +1998,16 @@ pub struct SafetyBufferingEvent {
     pub faster_model: Option<String>,
 }
 
+/// Fork: names the provider and model that produced a model response, as
+/// reported by the provider rather than as requested.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct ServedByEvent {
+    /// Provider id, e.g. `openrouter`.
+    pub provider: String,
+    /// Model the provider reports having used.
+    pub model: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ContextCompactedEvent;
 
@@ -126,6 +126,7 @@ pub(super) fn server_notification_thread_target(
         ServerNotification::ModelSafetyBufferingUpdated(notification) => {
             Some(notification.thread_id.as_str())
         }
+        ServerNotification::ModelServedBy(notification) => Some(notification.thread_id.as_str()),
         ServerNotification::TurnModerationMetadata(notification) => {
             Some(notification.thread_id.as_str())
         }
@@ -589,6 +589,8 @@ pub(crate) struct ChatWidget {
     unified_exec_wait_streak: Option<UnifiedExecWaitStreak>,
     turn_lifecycle: TurnLifecycleState,
     safety_buffering: SafetyBufferingState,
+    // Fork: last (provider, model) attribution shown in history.
+    last_served_by: Option<(String, String)>,
     task_complete_pending: bool,
     unified_exec_processes: Vec<UnifiedExecProcessSummary>,
     /// Tracks per-server MCP startup state while startup is in progress.
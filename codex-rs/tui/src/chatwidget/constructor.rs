@@ -155,6 +155,7 @@ impl ChatWidget {
             unified_exec_wait_streak: None,
             turn_lifecycle: TurnLifecycleState::new(prevent_idle_sleep),
             safety_buffering: SafetyBufferingState::default(),
+            last_served_by: None,
             task_complete_pending: false,
             unified_exec_processes: Vec::new(),
             mcp_startup_status: None,
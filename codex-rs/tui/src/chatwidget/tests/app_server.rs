@@ -472,6 +472,39 @@ async fn collab_spawn_end_shows_requested_model_and_effort() {
     );
 }
 
+#[tokio::test]
+async fn model_served_by_adds_info_only_when_attribution_changes() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(/*model_override*/ None).await;
+    let served_by = |provider: &str, model: &str| {
+        ServerNotification::ModelServedBy(ModelServedByNotification {
+            thread_id: "thread-1".to_string(),
+            turn_id: "turn-1".to_string(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+        })
+    };
+
+    chat.handle_server_notification(served_by("glm", "glm-5"), /*replay_kind*/ None);
+    chat.handle_server_notification(served_by("glm", "glm-5"), /*replay_kind*/ None);
+    chat.handle_server_notification(served_by("kimi", "kimi-k2"), /*replay_kind*/ None);
+    chat.handle_server_notification(
+        served_by("minimax", "minimax-m2"),
+        Some(ReplayKind::ResumeInitialMessages),
+    );
+
+    let rendered = drain_insert_history(&mut rx)
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rendered.len(),
+        2,
+        "expected two attribution cells, got {rendered:?}"
+    );
+    assert!(rendered[0].contains("Served by glm · glm-5"));
+    assert!(rendered[1].contains("Served by kimi · kimi-k2"));
+}
+
 #[tokio::test]
 async fn live_app_server_user_message_item_completed_does_not_duplicate_rendered_prompt() {
     let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual(/*model_override*/ None).await;
@@ -475,6 +475,28 @@ impl ChatWidget {
         }
     }
 
+    /// Fork: notes which provider and model served the response, only when it changes.
+    pub(super) fn on_model_served_by(
+        &mut self,
+        provider: String,
+        model: String,
+        replay_kind: Option<ReplayKind>,
+    ) {
+        if matches!(replay_kind, Some(ReplayKind::ResumeInitialMessages)) {
+            return;
+        }
+        let served_by = (provider, model);
+        if self.last_served_by.as_ref() == Some(&served_by) {
+            return;
+        }
+        let (provider, model) = &served_by;
+        self.add_info_message(
+            format!("Served by {provider} · {model}"),
+            /*hint*/ None,
+        );
+        self.last_served_by = Some(served_by);
+    }
+
     pub(super) fn on_plan_update(&mut self, update: UpdatePlanArgs) {
         self.transcript.saw_plan_update_this_turn = true;
         let total = update.plan.len();